@@ -0,0 +1,83 @@
+use advanced_data_structures::heaps::fibonacci_heap::FibonacciHeap;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+#[allow(dead_code)]
+mod rc_heap;
+
+const SIZES: [usize; 2] = [1_000, 10_000];
+
+// A fixed permutation of 0..n, so both heaps see the same pushes.
+fn values(n: usize) -> Vec<usize> {
+    (0..n).map(|i| (i * 7919) % n).collect()
+}
+
+fn push_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fibonacci_heap/push_pop");
+    for n in SIZES {
+        let values = values(n);
+        group.bench_with_input(BenchmarkId::new("arena", n), &values, |b, values| {
+            b.iter(|| {
+                let mut heap = FibonacciHeap::new();
+                for &value in values {
+                    heap.push(value);
+                }
+                while let Some(value) = heap.pop() {
+                    black_box(value);
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("rc", n), &values, |b, values| {
+            b.iter(|| {
+                let mut heap = rc_heap::FibonacciHeap::new();
+                for &value in values {
+                    heap.push(value);
+                }
+                while let Some(value) = heap.pop() {
+                    black_box(value);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+// Interleaves pops with decrease_key calls the way Dijkstra's algorithm
+// does, so cascading cuts happen inside trees built by consolidation. Every
+// pop takes an already decreased value, so no handle is used after its
+// value has left the heap.
+fn decrease_key(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fibonacci_heap/decrease_key");
+    for n in SIZES {
+        let values = values(n);
+        group.bench_with_input(BenchmarkId::new("arena", n), &values, |b, values| {
+            b.iter(|| {
+                let mut heap = FibonacciHeap::new();
+                let handles: Vec<_> = values.iter().map(|&value| heap.push(value + n)).collect();
+                heap.pop();
+                for (i, handle) in handles.iter().enumerate().skip(1) {
+                    heap.decrease_key(*handle, values[i]);
+                    if i % 4 == 0 {
+                        black_box(heap.pop());
+                    }
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("rc", n), &values, |b, values| {
+            b.iter(|| {
+                let mut heap = rc_heap::FibonacciHeap::new();
+                let handles: Vec<_> = values.iter().map(|&value| heap.push(value + n)).collect();
+                heap.pop();
+                for (i, handle) in handles.iter().enumerate().skip(1) {
+                    heap.decrease_key(handle, values[i]);
+                    if i % 4 == 0 {
+                        black_box(heap.pop());
+                    }
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, push_pop, decrease_key);
+criterion_main!(benches);
@@ -0,0 +1,295 @@
+// The reference-counted `FibonacciHeap` that the arena-backed one replaced,
+// kept here so the benchmarks can measure one against the other.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::{Rc, Weak};
+
+use advanced_data_structures::utils::compare::{Compare, Natural};
+
+type NodeRef<T> = Rc<RefCell<Node<T>>>;
+type Link<T> = Option<NodeRef<T>>;
+
+// A min-heap: `pop` returns the least value under the heap's comparator.
+#[derive(Debug)]
+pub struct FibonacciHeap<T, C = Natural> {
+    min: Link<T>,
+    nodes: usize,
+    compare: C,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    value: T,
+    degree: usize,
+    mark: bool,
+    parent: Option<Weak<RefCell<Node<T>>>>,
+    child: Link<T>,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+// Refers to a value pushed onto a `FibonacciHeap`, for use with
+// `decrease_key` and `delete`. A handle does not keep its value alive, so
+// using one after the value has left the heap panics.
+#[derive(Debug)]
+pub struct Handle<T>(Weak<RefCell<Node<T>>>);
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(Weak::clone(&self.0))
+    }
+}
+
+impl<T: Ord> FibonacciHeap<T> {
+    pub fn new() -> Self {
+        FibonacciHeap::with_comparator(Natural)
+    }
+}
+
+impl<T, C: Compare<T>> FibonacciHeap<T, C> {
+    // Creates an empty heap ordered by `compare` instead of `Ord`.
+    pub fn with_comparator(compare: C) -> Self {
+        FibonacciHeap {
+            min: None,
+            nodes: 0,
+            compare,
+        }
+    }
+
+    pub fn push(&mut self, value: T) -> Handle<T> {
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            degree: 0,
+            mark: false,
+            parent: None,
+            child: None,
+            left: None,
+            right: None,
+        }));
+        let handle = Handle(Rc::downgrade(&node));
+
+        self.insert_into_root_list(node);
+        self.nodes += 1;
+        handle
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let min = self.min.take()?;
+        self.min = detach(&min);
+
+        // Every child of the old minimum becomes a root.
+        let mut child = min.borrow_mut().child.take();
+        while let Some(node) = child {
+            child = detach(&node);
+            self.insert_into_root_list(node);
+        }
+
+        if self.min.is_some() {
+            self.consolidate();
+        }
+
+        self.nodes -= 1;
+        Some(Rc::try_unwrap(min).ok().unwrap().into_inner().value)
+    }
+
+    // Lowers the value behind `handle` to `value` in O(1) amortized time.
+    // Panics if `value` is greater than the current value.
+    pub fn decrease_key(&mut self, handle: &Handle<T>, value: T) {
+        let node = Self::upgrade(handle);
+        assert!(
+            self.compare.compare(&value, &node.borrow().value) != Ordering::Greater,
+            "decrease_key called with a greater value"
+        );
+        node.borrow_mut().value = value;
+
+        let parent = node.borrow().parent.as_ref().and_then(Weak::upgrade);
+        if let Some(parent) = parent {
+            if self.compare.compare(&node.borrow().value, &parent.borrow().value) == Ordering::Less {
+                self.cut(&node, &parent);
+                self.cascading_cut(parent);
+            }
+        }
+
+        let min = self.min.as_ref().unwrap();
+        if self.compare.compare(&node.borrow().value, &min.borrow().value) == Ordering::Less {
+            self.min = Some(node);
+        }
+    }
+
+    // Removes and returns the value behind `handle` in O(log n) amortized
+    // time.
+    pub fn delete(&mut self, handle: &Handle<T>) -> T {
+        let node = Self::upgrade(handle);
+
+        let parent = node.borrow().parent.as_ref().and_then(Weak::upgrade);
+        if let Some(parent) = parent {
+            self.cut(&node, &parent);
+            self.cascading_cut(parent);
+        }
+
+        // The node is now a root, so treating it as the minimum lets `pop`
+        // remove it; consolidation then finds the real minimum again.
+        self.min = Some(node);
+        self.pop().unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    fn upgrade(handle: &Handle<T>) -> NodeRef<T> {
+        handle.0.upgrade().expect("handle refers to a value no longer in the heap")
+    }
+
+    fn insert_into_root_list(&mut self, node: NodeRef<T>) {
+        {
+            let mut inner = node.borrow_mut();
+            inner.parent = None;
+            inner.mark = false;
+        }
+
+        if let Some(ref min) = self.min {
+            splice(min, &node);
+
+            if self.compare.compare(&node.borrow().value, &min.borrow().value) == Ordering::Less {
+                self.min = Some(node);
+            }
+        } else {
+            node.borrow_mut().left = Some(Rc::clone(&node));
+            node.borrow_mut().right = Some(Rc::clone(&node));
+            self.min = Some(node);
+        }
+    }
+
+    fn consolidate(&mut self) {
+        let mut roots = Vec::new();
+        let mut current = self.min.take();
+        while let Some(node) = current {
+            current = detach(&node);
+            roots.push(node);
+        }
+
+        let mut degrees: Vec<Link<T>> = Vec::new();
+        for mut x in roots {
+            let mut degree = x.borrow().degree;
+
+            while let Some(mut y) = degrees.get_mut(degree).and_then(Option::take) {
+                if self.compare.compare(&x.borrow().value, &y.borrow().value) == Ordering::Greater {
+                    std::mem::swap(&mut x, &mut y);
+                }
+
+                link(y, &x);
+                degree += 1;
+            }
+
+            if degree >= degrees.len() {
+                degrees.resize_with(degree + 1, || None);
+            }
+
+            degrees[degree] = Some(x);
+        }
+
+        for node in degrees.into_iter().flatten() {
+            self.insert_into_root_list(node);
+        }
+    }
+
+    // Moves `node` from the children of `parent` to the root list.
+    fn cut(&mut self, node: &NodeRef<T>, parent: &NodeRef<T>) {
+        let next = detach(node);
+        {
+            let mut parent = parent.borrow_mut();
+            if parent.child.as_ref().is_some_and(|child| Rc::ptr_eq(child, node)) {
+                parent.child = next;
+            }
+            parent.degree -= 1;
+        }
+
+        self.insert_into_root_list(Rc::clone(node));
+    }
+
+    // Walks up from a node that just lost a child, cutting every ancestor
+    // that had already lost one and marking the first one that had not.
+    fn cascading_cut(&mut self, mut node: NodeRef<T>) {
+        loop {
+            let parent = node.borrow().parent.as_ref().and_then(Weak::upgrade);
+            let Some(parent) = parent else {
+                break;
+            };
+
+            if !node.borrow().mark {
+                node.borrow_mut().mark = true;
+                break;
+            }
+
+            self.cut(&node, &parent);
+            node = parent;
+        }
+    }
+}
+
+impl<T, C> Drop for FibonacciHeap<T, C> {
+    // The sibling lists are cycles of strong references, so they have to be
+    // taken apart by hand for the nodes to be freed.
+    fn drop(&mut self) {
+        let mut stack: Vec<NodeRef<T>> = self.min.take().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let mut inner = node.borrow_mut();
+            stack.extend(inner.child.take());
+            stack.extend(inner.right.take());
+            inner.left = None;
+        }
+    }
+}
+
+// Inserts the detached `node` into the sibling list containing `anchor`,
+// just to its left.
+fn splice<T>(anchor: &NodeRef<T>, node: &NodeRef<T>) {
+    let left = anchor.borrow().left.clone().unwrap();
+    {
+        let mut inner = node.borrow_mut();
+        inner.left = Some(Rc::clone(&left));
+        inner.right = Some(Rc::clone(anchor));
+    }
+    left.borrow_mut().right = Some(Rc::clone(node));
+    anchor.borrow_mut().left = Some(Rc::clone(node));
+}
+
+// Removes `node` from its sibling list and returns one of the siblings it
+// leaves behind, if any.
+fn detach<T>(node: &NodeRef<T>) -> Link<T> {
+    let left = node.borrow_mut().left.take().unwrap();
+    let right = node.borrow_mut().right.take().unwrap();
+    if Rc::ptr_eq(&right, node) {
+        return None;
+    }
+
+    left.borrow_mut().right = Some(Rc::clone(&right));
+    right.borrow_mut().left = Some(left);
+    Some(right)
+}
+
+// Makes the detached root `y` a child of `x`.
+fn link<T>(y: NodeRef<T>, x: &NodeRef<T>) {
+    {
+        let mut inner = y.borrow_mut();
+        inner.parent = Some(Rc::downgrade(x));
+        inner.mark = false;
+    }
+
+    let child = x.borrow().child.clone();
+    if let Some(child) = child {
+        splice(&child, &y);
+    } else {
+        y.borrow_mut().left = Some(Rc::clone(&y));
+        y.borrow_mut().right = Some(Rc::clone(&y));
+        x.borrow_mut().child = Some(y);
+    }
+
+    x.borrow_mut().degree += 1;
+}
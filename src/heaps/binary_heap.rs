@@ -1,11 +1,37 @@
+use std::cmp::Ordering;
+
+use crate::utils::compare::{Compare, Natural, Reversed};
+
+use super::priority_queue::PriorityQueue;
+
+// `pop` returns the value that comes first under the heap's comparator. The
+// default comparator is `Reversed`, so `new` builds a max-heap and
+// `with_comparator(Natural)` a min-heap.
 #[derive(Debug, Clone)]
-pub struct BinaryHeap<T> {
+pub struct BinaryHeap<T, C = Reversed> {
     data: Vec<T>,
+    compare: C,
 }
 
 impl<T: Ord> BinaryHeap<T> {
     pub fn new() -> Self {
-        BinaryHeap { data: Vec::new() }
+        BinaryHeap::with_comparator(Reversed(Natural))
+    }
+}
+
+impl<T: Ord> Default for BinaryHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Compare<T>> BinaryHeap<T, C> {
+    // Creates an empty heap ordered by `compare` instead of `Ord`.
+    pub fn with_comparator(compare: C) -> Self {
+        BinaryHeap {
+            data: Vec::new(),
+            compare,
+        }
     }
 
     pub fn push(&mut self, item: T) {
@@ -15,7 +41,8 @@ impl<T: Ord> BinaryHeap<T> {
 
     pub fn pop(&mut self) -> Option<T> {
         if self.data.len() > 1 {
-            self.data.swap(0, self.data.len() - 1);
+            let last = self.data.len() - 1;
+            self.data.swap(0, last);
             let item = self.data.pop();
             self.heapify_down(0);
             item
@@ -27,7 +54,7 @@ impl<T: Ord> BinaryHeap<T> {
     fn heapify_up(&mut self, mut index: usize) {
         while index > 0 {
             let parent = (index - 1) / 2;
-            if self.data[index] > self.data[parent] {
+            if self.less(index, parent) {
                 self.data.swap(index, parent);
                 index = parent;
             } else {
@@ -38,34 +65,83 @@ impl<T: Ord> BinaryHeap<T> {
 
     fn heapify_down(&mut self, mut index: usize) {
         let len = self.data.len();
-        let mut largest = index;
+        let mut smallest = index;
 
         loop {
             let left = 2 * index + 1;
             let right = 2 * index + 2;
 
-            if left < len && self.data[left] > self.data[largest] {
-                largest = left;
+            if left < len && self.less(left, smallest) {
+                smallest = left;
             }
 
-            if right < len && self.data[right] > self.data[largest] {
-                largest = right;
+            if right < len && self.less(right, smallest) {
+                smallest = right;
             }
 
-            if largest != index {
-                self.data.swap(index, largest);
-                index = largest;
+            if smallest != index {
+                self.data.swap(index, smallest);
+                index = smallest;
             } else {
                 break;
             }
         }
     }
 
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.compare.compare(&self.data[a], &self.data[b]) == Ordering::Less
+    }
+
     pub fn peek(&self) -> Option<&T> {
-        self.data.get(0)
+        self.data.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
     }
 
     pub fn is_empty(&self) -> bool {
         self.data.is_empty()
     }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    // Moves every value of `other` into this heap, rebuilding it bottom-up
+    // in O(n + m).
+    pub fn merge(&mut self, other: Self) {
+        self.data.extend(other.data);
+        for index in (0..self.data.len() / 2).rev() {
+            self.heapify_down(index);
+        }
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue for BinaryHeap<T, C> {
+    type Item = T;
+
+    fn push(&mut self, value: T) {
+        BinaryHeap::push(self, value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        BinaryHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        BinaryHeap::peek(self)
+    }
+
+    fn len(&self) -> usize {
+        BinaryHeap::len(self)
+    }
+
+    fn clear(&mut self) {
+        BinaryHeap::clear(self);
+    }
+
+    fn merge(&mut self, other: Self) {
+        BinaryHeap::merge(self, other);
+    }
 }
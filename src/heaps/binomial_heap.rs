@@ -1,69 +1,183 @@
 use std::cmp::Ordering;
 
+use crate::utils::compare::{Compare, Natural};
+
+use super::priority_queue::PriorityQueue;
+
+// A min-heap: `pop` returns the least value under the heap's comparator.
+//
+// The heap is a list of binomial trees with strictly increasing degrees,
+// like the bits of a binary counter, so merging two heaps is binary
+// addition over their root lists and takes O(log n).
 #[derive(Debug, Clone)]
-pub struct BinomialHeap<T> {
+pub struct BinomialHeap<T, C = Natural> {
     roots: Vec<Node<T>>,
+    len: usize,
+    compare: C,
 }
 
+// A binomial tree of degree k has k children, of degrees 0 through k - 1,
+// stored in that order.
 #[derive(Debug, Clone)]
 struct Node<T> {
     value: T,
     children: Vec<Node<T>>,
 }
 
+impl<T> Node<T> {
+    fn degree(&self) -> usize {
+        self.children.len()
+    }
+}
+
 impl<T: Ord> BinomialHeap<T> {
     pub fn new() -> Self {
-        BinomialHeap { roots: Vec::new() }
+        BinomialHeap::with_comparator(Natural)
+    }
+}
+
+impl<T: Ord> Default for BinomialHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, C: Compare<T>> BinomialHeap<T, C> {
+    // Creates an empty heap ordered by `compare` instead of `Ord`.
+    pub fn with_comparator(compare: C) -> Self {
+        BinomialHeap {
+            roots: Vec::new(),
+            len: 0,
+            compare,
+        }
     }
 
     pub fn push(&mut self, value: T) {
-        let mut node = Node {
+        let node = Node {
             value,
             children: Vec::new(),
         };
 
-        self.roots.push(node);
-
-        let mut i = self.roots.len() - 1;
-        while i > 0 && self.roots[i].value < self.roots[i - 1].value {
-            self.roots.swap(i, i - 1);
-            i -= 1;
-        }
+        self.meld(vec![node]);
+        self.len += 1;
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        if self.roots.is_empty() {
-            return None;
-        }
+        let min = self.min_index()?;
+        let min = self.roots.remove(min);
 
-        let min = self.roots.remove(0);
+        // The children of a root already form a valid root list.
+        self.meld(min.children);
+        self.len -= 1;
+        Some(min.value)
+    }
 
-        for child in min.children.into_iter().rev() {
-            self.roots.push(child);
-        }
+    // Moves every value of `other` into this heap in O(log n).
+    pub fn merge(&mut self, other: Self) {
+        self.meld(other.roots);
+        self.len += other.len;
+    }
 
-        self.consolidate();
-        Some(min.value)
+    pub fn peek(&self) -> Option<&T> {
+        self.min_index().map(|i| &self.roots[i].value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    fn consolidate(&mut self) {
-        let mut i = 0;
+    pub fn clear(&mut self) {
+        self.roots.clear();
+        self.len = 0;
+    }
 
-        while i < self.roots.len() - 1 {
-            if self.roots[i].children.len() == self.roots[i + 1].children.len() {
-                let child = self.roots.remove(i + 1);
-                self.roots[i].children.push(child);
+    fn min_index(&self) -> Option<usize> {
+        (0..self.roots.len()).reduce(|min, i| {
+            if self.compare.compare(&self.roots[i].value, &self.roots[min].value) == Ordering::Less {
+                i
             } else {
-                i += 1;
+                min
+            }
+        })
+    }
+
+    // Adds the root list `other` to this heap's, linking trees of equal
+    // degree and carrying the result upward.
+    fn meld(&mut self, other: Vec<Node<T>>) {
+        let mut ours = std::mem::take(&mut self.roots).into_iter().peekable();
+        let mut theirs = other.into_iter().peekable();
+        let mut roots = Vec::with_capacity(ours.len().max(theirs.len()) + 1);
+        let mut carry: Option<Node<T>> = None;
+
+        loop {
+            let degree = [carry.as_ref(), ours.peek(), theirs.peek()]
+                .into_iter()
+                .flatten()
+                .map(Node::degree)
+                .min();
+            let Some(degree) = degree else {
+                break;
+            };
+
+            let mut trees = carry
+                .take()
+                .into_iter()
+                .chain(ours.next_if(|node| node.degree() == degree))
+                .chain(theirs.next_if(|node| node.degree() == degree));
+            let first = trees.next().unwrap();
+            match (trees.next(), trees.next()) {
+                (None, _) => roots.push(first),
+                (Some(second), None) => carry = Some(self.link(first, second)),
+                (Some(second), Some(third)) => {
+                    roots.push(first);
+                    carry = Some(self.link(second, third));
+                }
             }
         }
+
+        self.roots = roots;
     }
 
-    pub fn peek(&self) -> Option<&T> {
-        self.roots.first().map(|node| &node.value)
+    // Joins two trees of equal degree under the smaller root.
+    fn link(&self, a: Node<T>, b: Node<T>) -> Node<T> {
+        let (mut parent, child) = if self.compare.compare(&b.value, &a.value) == Ordering::Less {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        parent.children.push(child);
+        parent
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue for BinomialHeap<T, C> {
+    type Item = T;
+
+    fn push(&mut self, value: T) {
+        BinomialHeap::push(self, value);
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.roots.is_empty()
+    fn pop(&mut self) -> Option<T> {
+        BinomialHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        BinomialHeap::peek(self)
+    }
+
+    fn len(&self) -> usize {
+        BinomialHeap::len(self)
+    }
+
+    fn clear(&mut self) {
+        BinomialHeap::clear(self);
+    }
+
+    fn merge(&mut self, other: Self) {
+        BinomialHeap::merge(self, other);
     }
 }
@@ -1,14 +1,19 @@
-use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
-use std::rc::{Rc, Weak};
 
-type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+use crate::utils::compare::{Compare, Natural};
+use crate::utils::memory::{Arena, Id, Relocation};
 
+use super::priority_queue::PriorityQueue;
+
+// A min-heap: `pop` returns the least value under the heap's comparator.
+//
+// Nodes live in an arena and refer to each other by id, so sibling lists
+// are plain index cycles and a node is freed as soon as it leaves the heap.
 #[derive(Debug)]
-pub struct FibonacciHeap<T> {
-    min: Link<T>,
-    nodes: usize,
+pub struct FibonacciHeap<T, C = Natural> {
+    nodes: Arena<Node<T>>,
+    min: Option<Id>,
+    compare: C,
 }
 
 #[derive(Debug)]
@@ -16,130 +21,216 @@ struct Node<T> {
     value: T,
     degree: usize,
     mark: bool,
-    parent: Option<Weak<RefCell<Node<T>>>>,
-    child: Link<T>,
-    left: Link<T>,
-    right: Link<T>,
+    parent: Option<Id>,
+    child: Option<Id>,
+    left: Id,
+    right: Id,
+}
+
+// Refers to a value pushed onto a `FibonacciHeap`, for use with
+// `decrease_key` and `delete`. Using a handle after its value has left the
+// heap panics; handles from one heap must not be used with another, except
+// after translating them through the `HandleMap` that `merge` returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle(Id);
+
+// Translates handles into a heap that was merged into another one.
+#[derive(Debug, Clone, Copy)]
+pub struct HandleMap(Relocation);
+
+impl HandleMap {
+    pub fn get(&self, handle: Handle) -> Handle {
+        Handle(self.0.apply(handle.0))
+    }
 }
 
 impl<T: Ord> FibonacciHeap<T> {
     pub fn new() -> Self {
-        FibonacciHeap { min: None, nodes: 0 }
+        FibonacciHeap::with_comparator(Natural)
     }
+}
+
+impl<T: Ord> Default for FibonacciHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    pub fn push(&mut self, value: T) {
-        let node = Rc::new(RefCell::new(Node {
+impl<T, C: Compare<T>> FibonacciHeap<T, C> {
+    // Creates an empty heap ordered by `compare` instead of `Ord`.
+    pub fn with_comparator(compare: C) -> Self {
+        FibonacciHeap {
+            nodes: Arena::new(),
+            min: None,
+            compare,
+        }
+    }
+
+    pub fn push(&mut self, value: T) -> Handle {
+        let node = self.nodes.insert_with(|id| Node {
             value,
             degree: 0,
             mark: false,
             parent: None,
             child: None,
-            left: None,
-            right: None,
-        }));
+            left: id,
+            right: id,
+        });
 
-        if let Some(ref min) = self.min {
-            node.borrow_mut().right = Some(Rc::clone(min));
-            node.borrow_mut().left = min.borrow().left.clone();
+        self.insert_into_root_list(node);
+        Handle(node)
+    }
 
-            if let Some(ref left) = min.borrow().left {
-                left.borrow_mut().right = Some(Rc::clone(&node));
-            }
+    pub fn pop(&mut self) -> Option<T> {
+        let min = self.min.take()?;
+        self.min = self.detach(min);
 
-            min.borrow_mut().left = Some(Rc::clone(&node));
+        // Every child of the old minimum becomes a root.
+        let mut child = self.nodes[min].child.take();
+        while let Some(node) = child {
+            child = self.detach(node);
+            self.insert_into_root_list(node);
+        }
 
-            if node.borrow().value < min.borrow().value {
-                self.min = Some(Rc::clone(&node));
-            }
-        } else {
-            self.min = Some(Rc::clone(&node));
-            node.borrow_mut().left = Some(Rc::clone(&node));
-            node.borrow_mut().right = Some(Rc::clone(&node));
+        if self.min.is_some() {
+            self.consolidate();
         }
 
-        self.nodes += 1;
+        self.nodes.remove(min).map(|node| node.value)
     }
 
-    pub fn pop(&mut self) -> Option<T> {
-        if self.min.is_none() {
-            return None;
+    // Lowers the value behind `handle` to `value` in O(1) amortized time.
+    // Panics if `value` is greater than the current value.
+    pub fn decrease_key(&mut self, handle: Handle, value: T) {
+        let node = self.resolve(handle);
+        assert!(
+            self.compare.compare(&value, &self.nodes[node].value) != Ordering::Greater,
+            "decrease_key called with a greater value"
+        );
+        self.nodes[node].value = value;
+
+        if let Some(parent) = self.nodes[node].parent {
+            if self.less(node, parent) {
+                self.cut(node, parent);
+                self.cascading_cut(parent);
+            }
+        }
+
+        if self.less(node, self.min.unwrap()) {
+            self.min = Some(node);
         }
+    }
 
-        let min = self.min.take().unwrap();
+    // Removes and returns the value behind `handle` in O(log n) amortized
+    // time.
+    pub fn delete(&mut self, handle: Handle) -> T {
+        let node = self.resolve(handle);
 
-        while let Some(child) = min.borrow_mut().child.take() {
-            child.borrow_mut().parent = None;
-            self.insert_into_root_list(child);
+        if let Some(parent) = self.nodes[node].parent {
+            self.cut(node, parent);
+            self.cascading_cut(parent);
         }
 
-        if Rc::ptr_eq(&min, min.borrow().right.as_ref().unwrap()) {
-            self.min = None;
-        } else {
-            self.min = min.borrow().right.clone();
-            self.remove_from_root_list(Rc::clone(&min));
-            self.consolidate();
+        // The node is now a root, so treating it as the minimum lets `pop`
+        // remove it; consolidation then finds the real minimum again.
+        self.min = Some(node);
+        self.pop().unwrap()
+    }
+
+    // Moves every value of `other` into this heap. The root lists are joined
+    // in O(1) with a single comparison, but `other`'s nodes still have to be
+    // moved into this heap's arena, which takes O(m). Handles into `other`
+    // stop working; the returned map translates them into handles into this
+    // heap. Handles into this heap are unaffected.
+    pub fn merge(&mut self, other: Self) -> HandleMap {
+        let relocation = self.nodes.append(other.nodes, |node, relocation| {
+            node.parent = node.parent.map(|id| relocation.apply(id));
+            node.child = node.child.map(|id| relocation.apply(id));
+            node.left = relocation.apply(node.left);
+            node.right = relocation.apply(node.right);
+        });
+
+        if let Some(other_min) = other.min.map(|id| relocation.apply(id)) {
+            match self.min {
+                Some(min) => {
+                    self.join(min, other_min);
+                    if self.less(other_min, min) {
+                        self.min = Some(other_min);
+                    }
+                }
+                None => self.min = Some(other_min),
+            }
         }
+        HandleMap(relocation)
+    }
 
-        self.nodes -= 1;
-        Some(Rc::try_unwrap(min).ok().unwrap().into_inner().value)
+    pub fn peek(&self) -> Option<&T> {
+        self.min.map(|min| &self.nodes[min].value)
     }
 
-    fn insert_into_root_list(&mut self, node: Rc<RefCell<Node<T>>>) {
-        if let Some(ref min) = self.min {
-            node.borrow_mut().right = Some(Rc::clone(min));
-            node.borrow_mut().left = min.borrow().left.clone();
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.nodes.get(handle.0).map(|node| &node.value)
+    }
 
-            if let Some(ref left) = min.borrow().left {
-                left.borrow_mut().right = Some(Rc::clone(&node));
-            }
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
 
-            min.borrow_mut().left = Some(Rc::clone(&node));
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
 
-            if node.borrow().value < min.borrow().value {
-                self.min = Some(Rc::clone(&node));
-            }
-        } else {
-            self.min = Some(Rc::clone(&node));
-            node.borrow_mut().left = Some(Rc::clone(&node));
-            node.borrow_mut().right = Some(Rc::clone(&node));
-        }
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.min = None;
     }
 
-    fn remove_from_root_list(&mut self, node: Rc<RefCell<Node<T>>>) {
-        let left = node.borrow().left.clone().unwrap();
-        let right = node.borrow().right.clone().unwrap();
+    fn resolve(&self, handle: Handle) -> Id {
+        assert!(self.nodes.contains(handle.0), "handle refers to a value no longer in the heap");
+        handle.0
+    }
 
-        left.borrow_mut().right = Some(right.clone());
-        right.borrow_mut().left = Some(left.clone());
+    fn less(&self, a: Id, b: Id) -> bool {
+        self.compare.compare(&self.nodes[a].value, &self.nodes[b].value) == Ordering::Less
+    }
 
-        if Rc::ptr_eq(&node, &self.min.clone().unwrap()) {
-            self.min = Some(right);
+    fn insert_into_root_list(&mut self, node: Id) {
+        self.nodes[node].parent = None;
+        self.nodes[node].mark = false;
+
+        match self.min {
+            Some(min) => {
+                self.splice(min, node);
+                if self.less(node, min) {
+                    self.min = Some(node);
+                }
+            }
+            None => {
+                self.nodes[node].left = node;
+                self.nodes[node].right = node;
+                self.min = Some(node);
+            }
         }
     }
 
     fn consolidate(&mut self) {
-        let mut degrees = vec![None; self.nodes];
-        let mut nodes = Vec::new();
-        let start = self.min.clone().unwrap();
-        nodes.push(Rc::clone(&start));
-
-        let mut current = start.borrow().right.clone().unwrap();
-        while !Rc::ptr_eq(&current, &start) {
-            nodes.push(Rc::clone(&current));
-            current = current.borrow().right.clone().unwrap();
+        let mut roots = Vec::new();
+        let mut current = self.min.take();
+        while let Some(node) = current {
+            current = self.detach(node);
+            roots.push(node);
         }
 
-        for node in nodes {
-            let mut x = Rc::clone(&node);
-            let mut degree = x.borrow().degree;
+        let mut degrees: Vec<Option<Id>> = Vec::new();
+        for mut x in roots {
+            let mut degree = self.nodes[x].degree;
 
-            while let Some(Some(mut y)) = degrees.get_mut(degree) {
-                if x.borrow().value > y.borrow().value {
+            while let Some(mut y) = degrees.get_mut(degree).and_then(Option::take) {
+                if self.less(y, x) {
                     std::mem::swap(&mut x, &mut y);
                 }
 
-                self.link(y, Rc::clone(&x));
-                *y.borrow_mut() = None;
+                self.link(y, x);
                 degree += 1;
             }
 
@@ -147,40 +238,116 @@ impl<T: Ord> FibonacciHeap<T> {
                 degrees.resize(degree + 1, None);
             }
 
-            degrees[degree] = Some(Rc::clone(&x));
+            degrees[degree] = Some(x);
         }
 
-        self.min = None;
-
         for node in degrees.into_iter().flatten() {
             self.insert_into_root_list(node);
         }
     }
 
-    fn link(&mut self, y: Rc<RefCell<Node<T>>>, x: Rc<RefCell<Node<T>>>) {
-        self.remove_from_root_list(Rc::clone(&y));
+    // Moves `node` from the children of `parent` to the root list.
+    fn cut(&mut self, node: Id, parent: Id) {
+        let next = self.detach(node);
+        if self.nodes[parent].child == Some(node) {
+            self.nodes[parent].child = next;
+        }
+        self.nodes[parent].degree -= 1;
 
-        y.borrow_mut().left = None;
-        y.borrow_mut().right = None;
-        y.borrow_mut().parent = Some(Rc::downgrade(&x));
+        self.insert_into_root_list(node);
+    }
+
+    // Walks up from a node that just lost a child, cutting every ancestor
+    // that had already lost one and marking the first one that had not.
+    fn cascading_cut(&mut self, mut node: Id) {
+        while let Some(parent) = self.nodes[node].parent {
+            if !self.nodes[node].mark {
+                self.nodes[node].mark = true;
+                break;
+            }
 
-        if x.borrow().child.is_some() {
-            let child = x.borrow().child.as_ref().unwrap();
-            y.borrow_mut().right = Some(Rc::clone(child));
-            y.borrow_mut().left = child.borrow().left.clone();
+            self.cut(node, parent);
+            node = parent;
+        }
+    }
 
-            if let Some(ref left) = child.borrow().left {
-                left.borrow_mut().right = Some(Rc::clone(&y));
+    // Makes the detached root `y` a child of `x`.
+    fn link(&mut self, y: Id, x: Id) {
+        self.nodes[y].parent = Some(x);
+        self.nodes[y].mark = false;
+
+        match self.nodes[x].child {
+            Some(child) => self.splice(child, y),
+            None => {
+                self.nodes[y].left = y;
+                self.nodes[y].right = y;
+                self.nodes[x].child = Some(y);
             }
+        }
+
+        self.nodes[x].degree += 1;
+    }
 
-            child.borrow_mut().left = Some(Rc::clone(&y));
-        } else {
-            x.borrow_mut().child = Some(Rc::clone(&y));
-            y.borrow_mut().left = Some(Rc::clone(&y));
-            y.borrow_mut().right = Some(Rc::clone(&y));
+    // Inserts the detached `node` into the sibling list containing `anchor`,
+    // just to its left.
+    fn splice(&mut self, anchor: Id, node: Id) {
+        let left = self.nodes[anchor].left;
+        self.nodes[node].left = left;
+        self.nodes[node].right = anchor;
+        self.nodes[left].right = node;
+        self.nodes[anchor].left = node;
+    }
+
+    // Joins the disjoint sibling lists containing `a` and `b` into one.
+    fn join(&mut self, a: Id, b: Id) {
+        let a_right = self.nodes[a].right;
+        let b_left = self.nodes[b].left;
+        self.nodes[a].right = b;
+        self.nodes[b].left = a;
+        self.nodes[b_left].right = a_right;
+        self.nodes[a_right].left = b_left;
+    }
+
+    // Removes `node` from its sibling list and returns one of the siblings it
+    // leaves behind, if any.
+    fn detach(&mut self, node: Id) -> Option<Id> {
+        let Node { left, right, .. } = self.nodes[node];
+        if right == node {
+            return None;
         }
 
-        x.borrow_mut().degree += 1;
-        y.borrow_mut().mark = false;
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+        self.nodes[node].left = node;
+        self.nodes[node].right = node;
+        Some(right)
+    }
+}
+
+impl<T, C: Compare<T>> PriorityQueue for FibonacciHeap<T, C> {
+    type Item = T;
+
+    fn push(&mut self, value: T) {
+        FibonacciHeap::push(self, value);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        FibonacciHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        FibonacciHeap::peek(self)
+    }
+
+    fn len(&self) -> usize {
+        FibonacciHeap::len(self)
+    }
+
+    fn clear(&mut self) {
+        FibonacciHeap::clear(self);
+    }
+
+    fn merge(&mut self, other: Self) {
+        FibonacciHeap::merge(self, other);
     }
 }
@@ -0,0 +1,204 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::utils::compare::{Compare, Natural, Reversed};
+
+use super::priority_queue::PriorityQueue;
+
+// A heap of keys ordered by priority that also maps every key to its slot
+// in the heap. Each key appears at most once, and its priority can be
+// changed or the key removed in O(log n). Like `BinaryHeap` it defaults to
+// the `Reversed` comparator, so `new` pops the greatest priority first.
+#[derive(Debug, Clone)]
+pub struct IndexedBinaryHeap<K, P, C = Reversed> {
+    data: Vec<(K, P)>,
+    positions: HashMap<K, usize>,
+    compare: C,
+}
+
+impl<K: Hash + Eq + Clone, P: Ord> IndexedBinaryHeap<K, P> {
+    pub fn new() -> Self {
+        IndexedBinaryHeap::with_comparator(Reversed(Natural))
+    }
+}
+
+impl<K: Hash + Eq + Clone, P: Ord> Default for IndexedBinaryHeap<K, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone, P, C: Compare<P>> IndexedBinaryHeap<K, P, C> {
+    // Creates an empty heap ordering priorities by `compare` instead of
+    // `Ord`.
+    pub fn with_comparator(compare: C) -> Self {
+        IndexedBinaryHeap {
+            data: Vec::new(),
+            positions: HashMap::new(),
+            compare,
+        }
+    }
+
+    // Adds `key` with `priority`. If the key is already present its
+    // priority is replaced instead, and the old one is returned.
+    pub fn push(&mut self, key: K, priority: P) -> Option<P> {
+        if self.positions.contains_key(&key) {
+            return self.change_priority(&key, priority);
+        }
+
+        self.positions.insert(key.clone(), self.data.len());
+        self.data.push((key, priority));
+        self.heapify_up(self.data.len() - 1);
+        None
+    }
+
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        Some(self.remove_at(0))
+    }
+
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        self.data.first().map(|(key, priority)| (key, priority))
+    }
+
+    // Sets the priority of `key` and returns the old one, or returns `None`
+    // without changing anything if the key is not in the heap.
+    pub fn change_priority(&mut self, key: &K, priority: P) -> Option<P> {
+        let index = *self.positions.get(key)?;
+        let old = std::mem::replace(&mut self.data[index].1, priority);
+        self.restore(index);
+        Some(old)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<P> {
+        let index = *self.positions.get(key)?;
+        Some(self.remove_at(index).1)
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&P> {
+        self.positions.get(key).map(|&index| &self.data[index].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.positions.clear();
+    }
+
+    // Moves every entry of `other` into this heap. Keys present in both
+    // take their priority from `other`.
+    pub fn merge(&mut self, other: Self) {
+        for (key, priority) in other.data {
+            self.push(key, priority);
+        }
+    }
+
+    fn remove_at(&mut self, index: usize) -> (K, P) {
+        let last = self.data.len() - 1;
+        self.swap(index, last);
+
+        let (key, priority) = self.data.pop().unwrap();
+        self.positions.remove(&key);
+        if index < self.data.len() {
+            self.restore(index);
+        }
+        (key, priority)
+    }
+
+    // Moves the entry at `index` up or down to where its priority belongs.
+    fn restore(&mut self, index: usize) {
+        let index = self.heapify_up(index);
+        self.heapify_down(index);
+    }
+
+    fn heapify_up(&mut self, mut index: usize) -> usize {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.less(index, parent) {
+                self.swap(index, parent);
+                index = parent;
+            } else {
+                break;
+            }
+        }
+        index
+    }
+
+    fn heapify_down(&mut self, mut index: usize) {
+        let len = self.data.len();
+
+        loop {
+            let left = 2 * index + 1;
+            let right = 2 * index + 2;
+            let mut smallest = index;
+
+            if left < len && self.less(left, smallest) {
+                smallest = left;
+            }
+
+            if right < len && self.less(right, smallest) {
+                smallest = right;
+            }
+
+            if smallest == index {
+                break;
+            }
+
+            self.swap(index, smallest);
+            index = smallest;
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.data.swap(a, b);
+        *self.positions.get_mut(&self.data[a].0).unwrap() = a;
+        *self.positions.get_mut(&self.data[b].0).unwrap() = b;
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        self.compare.compare(&self.data[a].1, &self.data[b].1) == Ordering::Less
+    }
+}
+
+impl<K: Hash + Eq + Clone, P, C: Compare<P>> PriorityQueue for IndexedBinaryHeap<K, P, C> {
+    type Item = (K, P);
+
+    fn push(&mut self, (key, priority): (K, P)) {
+        IndexedBinaryHeap::push(self, key, priority);
+    }
+
+    fn pop(&mut self) -> Option<(K, P)> {
+        IndexedBinaryHeap::pop(self)
+    }
+
+    fn peek(&self) -> Option<&(K, P)> {
+        self.data.first()
+    }
+
+    fn len(&self) -> usize {
+        IndexedBinaryHeap::len(self)
+    }
+
+    fn clear(&mut self) {
+        IndexedBinaryHeap::clear(self);
+    }
+
+    fn merge(&mut self, other: Self) {
+        IndexedBinaryHeap::merge(self, other);
+    }
+}
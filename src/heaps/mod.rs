@@ -1,3 +1,5 @@
 pub mod binary_heap;
+pub mod indexed_binary_heap;
 pub mod fibonacci_heap;
 pub mod binomial_heap;
+pub mod priority_queue;
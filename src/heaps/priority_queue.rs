@@ -0,0 +1,68 @@
+// The operations shared by the heaps in this module, so that code written
+// against `PriorityQueue` can run on any of them.
+//
+// `pop` returns the item that comes first under the queue's comparator, so
+// the comparator alone decides the order; wrapping it in `Reversed` pops
+// from the other end.
+pub trait PriorityQueue {
+    type Item;
+
+    fn push(&mut self, value: Self::Item);
+
+    fn pop(&mut self) -> Option<Self::Item>;
+
+    fn peek(&self) -> Option<&Self::Item>;
+
+    fn len(&self) -> usize;
+
+    // Removes every item in O(n), without popping them one at a time.
+    fn clear(&mut self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Moves every value of `other` into this queue.
+    fn merge(&mut self, other: Self)
+    where
+        Self: Sized;
+
+    // Consumes the queue, returning its values in the order `pop` would.
+    fn into_sorted_vec(mut self) -> Vec<Self::Item>
+    where
+        Self: Sized,
+    {
+        self.drain().collect()
+    }
+
+    // Empties the queue, yielding its values in the order `pop` would.
+    // Values left unconsumed when the iterator is dropped are discarded.
+    fn drain(&mut self) -> Drain<'_, Self> {
+        Drain { queue: self }
+    }
+}
+
+pub struct Drain<'a, Q: PriorityQueue + ?Sized> {
+    queue: &'a mut Q,
+}
+
+impl<Q: PriorityQueue + ?Sized> Iterator for Drain<'_, Q> {
+    type Item = Q::Item;
+
+    fn next(&mut self) -> Option<Q::Item> {
+        self.queue.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.queue.len();
+        (len, Some(len))
+    }
+}
+
+impl<Q: PriorityQueue + ?Sized> ExactSizeIterator for Drain<'_, Q> {}
+
+impl<Q: PriorityQueue + ?Sized> Drop for Drain<'_, Q> {
+    fn drop(&mut self) {
+        self.queue.clear();
+    }
+}
@@ -1,62 +1,439 @@
 use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::ops::RangeBounds;
+
+use super::binary_node::{self, BinaryNode, Join};
+use super::invariant::InvariantViolation;
+use super::owned_iter::{Open, OwnedIter, Part};
+use super::summary::Summary;
+use crate::utils::compare::{Compare, Natural};
+
+type Link<T, S> = Option<Box<TreeNode<T, S>>>;
 
 #[derive(Debug, Clone)]
-pub struct AVLTree<T> {
-    root: Option<Box<TreeNode<T>>>,
+pub struct AVLTree<T, S = (), C = Natural> {
+    root: Link<T, S>,
+    compare: C,
 }
 
 #[derive(Debug, Clone)]
-struct TreeNode<T> {
+pub(crate) struct TreeNode<T, S> {
     value: T,
     height: i32,
-    left: Option<Box<TreeNode<T>>>,
-    right: Option<Box<TreeNode<T>>>,
+    size: usize,
+    summary: S,
+    left: Link<T, S>,
+    right: Link<T, S>,
 }
 
 impl<T: Ord> AVLTree<T> {
     pub fn new() -> Self {
-        AVLTree { root: None }
+        AVLTree {
+            root: None,
+            compare: Natural,
+        }
+    }
+
+    // Builds a perfectly balanced tree in O(n) from values in ascending
+    // order. Repeated values are kept once; panics if the values are out of
+    // order.
+    pub fn from_sorted<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut values: Vec<T> = values.into_iter().collect();
+        values.dedup();
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]), "from_sorted requires values in ascending order");
+        let len = values.len();
+        AVLTree {
+            root: Self::build(&mut values.into_iter(), len),
+            compare: Natural,
+        }
+    }
+}
+
+impl<T: Ord> Default for AVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, S: Summary<T>> AVLTree<T, S> {
+    // Creates an empty tree that maintains a summary of type `S` over its
+    // values for `fold_range`.
+    pub fn with_summary() -> Self {
+        AVLTree {
+            root: None,
+            compare: Natural,
+        }
+    }
+}
+
+impl<T, C: Compare<T>> AVLTree<T, (), C> {
+    // Creates an empty tree ordered by `compare` instead of `Ord`.
+    pub fn with_comparator(compare: C) -> Self {
+        AVLTree { root: None, compare }
+    }
+}
+
+impl<T, S: Summary<T>, C: Compare<T>> AVLTree<T, S, C> {
+    // Creates an empty tree that both maintains a summary of type `S` and
+    // orders values by `compare`, combining `with_summary` and
+    // `with_comparator`.
+    pub fn with_summary_and_comparator(compare: C) -> Self {
+        AVLTree { root: None, compare }
+    }
+
+    fn build(values: &mut std::vec::IntoIter<T>, n: usize) -> Link<T, S> {
+        if n == 0 {
+            return None;
+        }
+        let left = Self::build(values, n / 2);
+        let value = values.next().unwrap();
+        let right = Self::build(values, n - n / 2 - 1);
+        let mut node = Self::new_node(value);
+        node.left = left;
+        node.right = right;
+        Self::update(&mut node);
+        Some(node)
+    }
+
+    fn new_node(value: T) -> Box<TreeNode<T, S>> {
+        Box::new(TreeNode {
+            summary: S::of(&value),
+            value,
+            height: 1,
+            size: 1,
+            left: None,
+            right: None,
+        })
     }
 
     pub fn insert(&mut self, value: T) {
-        self.root = Self::insert_node(self.root.take(), value);
+        if let Err(rank) = self.search_by(|other| self.compare.compare(&value, other)) {
+            self.insert_at(rank, value);
+        }
+    }
+
+    // Inserts `value` so that it becomes the value at in-order position
+    // `rank`. Callers pass the vacant position `search_by` reported.
+    pub(crate) fn insert_at(&mut self, rank: usize, value: T) {
+        self.root = Some(Self::insert_node(self.root.take(), rank, value));
     }
 
-    fn insert_node(node: Option<Box<TreeNode<T>>>, value: T) -> Option<Box<TreeNode<T>>> {
+    fn insert_node(node: Link<T, S>, rank: usize, value: T) -> Box<TreeNode<T, S>> {
         let mut node = match node {
             Some(node) => node,
-            None => return Some(Box::new(TreeNode {
-                value,
-                height: 1,
-                left: None,
-                right: None,
-            })),
+            None => return Self::new_node(value),
         };
 
-        match value.cmp(&node.value) {
+        let left_size = Self::size(&node.left);
+        if rank <= left_size {
+            node.left = Some(Self::insert_node(node.left.take(), rank, value));
+        } else {
+            node.right = Some(Self::insert_node(node.right.take(), rank - left_size - 1, value));
+        }
+
+        Self::update(&mut node);
+        Self::balance(node)
+    }
+
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let compare = &self.compare;
+        let (root, removed) = Self::remove_node(self.root.take(), &mut |other: &T| compare.compare(value, other));
+        self.root = root;
+        removed
+    }
+
+    // Removes the value `probe` reports as equal. `probe` orders the target
+    // relative to the stored value it is given, like `search_by`.
+    pub(crate) fn remove_by<F: FnMut(&T) -> Ordering>(&mut self, mut probe: F) -> Option<T> {
+        let (root, removed) = Self::remove_node(self.root.take(), &mut probe);
+        self.root = root;
+        removed
+    }
+
+    fn remove_node<F: FnMut(&T) -> Ordering>(node: Link<T, S>, probe: &mut F) -> (Link<T, S>, Option<T>) {
+        let mut node = match node {
+            Some(node) => node,
+            None => return (None, None),
+        };
+
+        let removed = match probe(&node.value) {
             Ordering::Less => {
-                node.left = Self::insert_node(node.left.take(), value);
+                let (left, removed) = Self::remove_node(node.left.take(), probe);
+                node.left = left;
+                removed
             }
             Ordering::Greater => {
-                node.right = Self::insert_node(node.right.take(), value);
+                let (right, removed) = Self::remove_node(node.right.take(), probe);
+                node.right = right;
+                removed
+            }
+            Ordering::Equal => {
+                let (left, right) = (node.left.take(), node.right.take());
+                match (left, right) {
+                    (None, right) => return (right, Some(node.value)),
+                    (left, None) => return (left, Some(node.value)),
+                    (Some(left), Some(right)) => {
+                        // Replace the removed node with its in-order successor.
+                        let (right, mut successor) = Self::remove_min_node(right);
+                        successor.left = Some(left);
+                        successor.right = right;
+                        let removed = std::mem::replace(&mut node, successor);
+                        Some(removed.value)
+                    }
+                }
             }
-            Ordering::Equal => return Some(node),
+        };
+
+        Self::update(&mut node);
+        (Some(Self::balance(node)), removed)
+    }
+
+    fn remove_min_node(mut node: Box<TreeNode<T, S>>) -> (Link<T, S>, Box<TreeNode<T, S>>) {
+        match node.left.take() {
+            Some(left) => {
+                let (left, min) = Self::remove_min_node(left);
+                node.left = left;
+                Self::update(&mut node);
+                (Some(Self::balance(node)), min)
+            }
+            None => (node.right.take(), node),
         }
+    }
 
+    fn remove_max_node(mut node: Box<TreeNode<T, S>>) -> (Link<T, S>, Box<TreeNode<T, S>>) {
+        match node.right.take() {
+            Some(right) => {
+                let (right, max) = Self::remove_max_node(right);
+                node.right = right;
+                Self::update(&mut node);
+                (Some(Self::balance(node)), max)
+            }
+            None => (node.left.take(), node),
+        }
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        let (root, min) = Self::remove_min_node(self.root.take()?);
+        self.root = root;
+        Some(min.value)
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        let (root, max) = Self::remove_max_node(self.root.take()?);
+        self.root = root;
+        Some(max.value)
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        let root = Self::union_nodes(&self.compare, self.root, other.root);
+        AVLTree { root, compare: self.compare }
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        let root = Self::intersection_nodes(&self.compare, self.root, other.root);
+        AVLTree { root, compare: self.compare }
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        let root = Self::difference_nodes(&self.compare, self.root, other.root);
+        AVLTree { root, compare: self.compare }
+    }
+
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        let root = Self::symmetric_difference_nodes(&self.compare, self.root, other.root);
+        AVLTree { root, compare: self.compare }
+    }
+
+    pub fn split_off(&mut self, value: &T) -> Self
+    where
+        C: Clone,
+    {
+        let (less, found, greater) = Self::split(&self.compare, self.root.take(), value);
+        self.root = less;
+        let greater = match found {
+            Some(node) => Some(Self::join(None, node, greater)),
+            None => greater,
+        };
+        AVLTree {
+            root: greater,
+            compare: self.compare.clone(),
+        }
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        // Concatenate directly when the key ranges do not overlap.
+        let before = matches!((self.iter().next_back(), other.iter().next()), (Some(a), Some(b)) if self.compare.compare(a, b) == Ordering::Less);
+        let after = matches!((other.iter().next_back(), self.iter().next()), (Some(a), Some(b)) if self.compare.compare(a, b) == Ordering::Less);
+        let (left, right) = (self.root.take(), other.root.take());
+        self.root = if before {
+            Self::join_pair(left, right)
+        } else if after {
+            Self::join_pair(right, left)
+        } else {
+            Self::union_nodes(&self.compare, left, right)
+        };
+    }
+
+    fn join_right(mut left: Box<TreeNode<T, S>>, mut mid: Box<TreeNode<T, S>>, right: Link<T, S>) -> Box<TreeNode<T, S>> {
+        if Self::height(&left.right) <= Self::height(&right) + 1 {
+            mid.left = left.right.take();
+            mid.right = right;
+            Self::update(&mut mid);
+            left.right = Some(mid);
+        } else {
+            left.right = Some(Self::join_right(left.right.take().unwrap(), mid, right));
+        }
+        Self::update(&mut left);
+        Self::balance(left)
+    }
+
+    fn join_left(left: Link<T, S>, mut mid: Box<TreeNode<T, S>>, mut right: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
+        if Self::height(&right.left) <= Self::height(&left) + 1 {
+            mid.left = left;
+            mid.right = right.left.take();
+            Self::update(&mut mid);
+            right.left = Some(mid);
+        } else {
+            right.left = Some(Self::join_left(left, mid, right.left.take().unwrap()));
+        }
+        Self::update(&mut right);
+        Self::balance(right)
+    }
+
+    fn update(node: &mut TreeNode<T, S>) {
         node.height = 1 + i32::max(Self::height(&node.left), Self::height(&node.right));
-        Some(Self::balance(node))
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+        node.summary = Self::summarize(node);
+    }
+
+    fn summarize(node: &TreeNode<T, S>) -> S {
+        let summary = S::of(&node.value);
+        let summary = match &node.left {
+            Some(left) => left.summary.combine(&summary),
+            None => summary,
+        };
+        match &node.right {
+            Some(right) => summary.combine(&right.summary),
+            None => summary,
+        }
+    }
+
+    fn size(node: &Link<T, S>) -> usize {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn rank(&self, value: &T) -> usize {
+        let mut current = &self.root;
+        let mut rank = 0;
+        while let Some(node) = current {
+            if self.compare.compare(value, &node.value) != Ordering::Greater {
+                current = &node.left;
+            } else {
+                rank += Self::size(&node.left) + 1;
+                current = &node.right;
+            }
+        }
+        rank
+    }
+
+    pub fn select(&self, k: usize) -> Option<&T> {
+        let mut current = &self.root;
+        let mut k = k;
+        while let Some(node) = current {
+            let left_size = Self::size(&node.left);
+            match k.cmp(&left_size) {
+                Ordering::Less => current = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    current = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    // Callers must not change how the value orders or summarizes.
+    pub(crate) fn select_mut(&mut self, k: usize) -> Option<&mut T> {
+        let mut current = &mut self.root;
+        let mut k = k;
+        while let Some(node) = current {
+            let left_size = Self::size(&node.left);
+            match k.cmp(&left_size) {
+                Ordering::Less => current = &mut node.left,
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    current = &mut node.right;
+                }
+            }
+        }
+        None
+    }
+
+    // Finds the in-order position of the value `probe` reports as equal, or
+    // the position a new value would take. `probe` orders the target relative
+    // to the stored value it is given, so callers can search by part of a
+    // value, such as a map key.
+    pub(crate) fn search_by<F: FnMut(&T) -> Ordering>(&self, mut probe: F) -> Result<usize, usize> {
+        let mut current = &self.root;
+        let mut rank = 0;
+        while let Some(node) = current {
+            match probe(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => {
+                    rank += Self::size(&node.left) + 1;
+                    current = &node.right;
+                }
+                Ordering::Equal => return Ok(rank + Self::size(&node.left)),
+            }
+        }
+        Err(rank)
+    }
+
+    pub(crate) fn get_by<F: FnMut(&T) -> Ordering>(&self, mut probe: F) -> Option<&T> {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match probe(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => current = &node.right,
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+        None
+    }
+
+    // Callers must not change how the value orders or summarizes.
+    pub(crate) fn get_by_mut<F: FnMut(&T) -> Ordering>(&mut self, mut probe: F) -> Option<&mut T> {
+        let mut current = &mut self.root;
+        while let Some(node) = current {
+            match probe(&node.value) {
+                Ordering::Less => current = &mut node.left,
+                Ordering::Greater => current = &mut node.right,
+                Ordering::Equal => return Some(&mut node.value),
+            }
+        }
+        None
     }
 
-    fn height(node: &Option<Box<TreeNode<T>>>) -> i32 {
+    fn height(node: &Link<T, S>) -> i32 {
         node.as_ref().map_or(0, |node| node.height)
     }
 
-    fn balance_factor(node: &Option<Box<TreeNode<T>>>) -> i32 {
+    fn balance_factor(node: &Link<T, S>) -> i32 {
         Self::height(&node.as_ref().unwrap().left) - Self::height(&node.as_ref().unwrap().right)
     }
 
-    fn balance(mut node: Box<TreeNode<T>>) -> Box<TreeNode<T>> {
-        let balance_factor = Self::balance_factor(&Some(node.clone()));
+    fn balance(mut node: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
+        let balance_factor = Self::height(&node.left) - Self::height(&node.right);
 
         if balance_factor > 1 {
             if Self::balance_factor(&node.left) < 0 {
@@ -75,54 +452,397 @@ impl<T: Ord> AVLTree<T> {
         node
     }
 
-    fn rotate_right(mut y: Box<TreeNode<T>>) -> Box<TreeNode<T>> {
+    fn rotate_right(mut y: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
         let mut x = y.left.take().unwrap();
         y.left = x.right.take();
         x.right = Some(y);
 
-        x.right.as_mut().unwrap().height = 1 + i32::max(Self::height(&x.right.as_ref().unwrap().left), Self::height(&x.right.as_ref().unwrap().right));
-        x.height = 1 + i32::max(Self::height(&x.left), Self::height(&x.right));
+        Self::update(x.right.as_mut().unwrap());
+        Self::update(&mut x);
 
         x
     }
 
-    fn rotate_left(mut x: Box<TreeNode<T>>) -> Box<TreeNode<T>> {
+    fn rotate_left(mut x: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
         let mut y = x.right.take().unwrap();
         x.right = y.left.take();
         y.left = Some(x);
 
-        y.left.as_mut().unwrap().height = 1 + i32::max(Self::height(&y.left.as_ref().unwrap().left), Self::height(&y.left.as_ref().unwrap().right));
-        y.height = 1 + i32::max(Self::height(&y.left), Self::height(&y.right));
+        Self::update(y.left.as_mut().unwrap());
+        Self::update(&mut y);
 
         y
     }
 
     pub fn contains(&self, value: &T) -> bool {
-        Self::contains_node(&self.root, value)
+        Self::contains_node(&self.compare, &self.root, value)
     }
 
-    fn contains_node(node: &Option<Box<TreeNode<T>>>, value: &T) -> bool {
+    fn contains_node(compare: &C, node: &Link<T, S>, value: &T) -> bool {
         match node {
-            Some(node) => match value.cmp(&node.value) {
-                Ordering::Less => Self::contains_node(&node.left, value),
-                Ordering::Greater => Self::contains_node(&node.right, value),
+            Some(node) => match compare.compare(value, &node.value) {
+                Ordering::Less => Self::contains_node(compare, &node.left, value),
+                Ordering::Greater => Self::contains_node(compare, &node.right, value),
                 Ordering::Equal => true,
             },
             None => false,
         }
     }
 
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T, R, S, C> {
+        Range {
+            inner: binary_node::Range::new(self.root.as_deref(), range, &self.compare),
+        }
+    }
+
+    // Combines the summaries of every value in `range`, in ascending order.
+    pub fn fold_range<R: RangeBounds<T>>(&self, range: R) -> S {
+        binary_node::fold(&self.compare, self.root.as_deref(), &range, false, false)
+    }
+
+    pub fn floor(&self, value: &T) -> Option<&T> {
+        binary_node::floor(&self.compare, self.root.as_deref(), value, false)
+    }
+
+    pub fn ceiling(&self, value: &T) -> Option<&T> {
+        binary_node::ceiling(&self.compare, self.root.as_deref(), value, false)
+    }
+
+    pub fn predecessor(&self, value: &T) -> Option<&T> {
+        binary_node::floor(&self.compare, self.root.as_deref(), value, true)
+    }
+
+    pub fn successor(&self, value: &T) -> Option<&T> {
+        binary_node::ceiling(&self.compare, self.root.as_deref(), value, true)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter.push_right(self.root.as_deref());
+        iter
+    }
+
+    // Starts an in-order walk that skips whole subtrees by their summary.
+    pub(crate) fn pruned(&self) -> Pruned<'_, T, S> {
+        Pruned {
+            stack: Vec::new(),
+            pending: self.root.as_deref(),
+        }
+    }
+
+    pub fn pre_order(&self) -> PreOrder<'_, T, S> {
+        PreOrder {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    pub fn post_order(&self) -> PostOrder<'_, T, S> {
+        PostOrder {
+            stack: self.root.as_deref().map(|node| (node, false)).into_iter().collect(),
+        }
+    }
+
+    pub fn level_order(&self) -> LevelOrder<'_, T, S> {
+        LevelOrder {
+            queue: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        Self::validate_node(&self.compare, &self.root, None, None).map(|_| ())
+    }
+
+    // Returns the height and size of a valid subtree whose values lie
+    // strictly between `lower` and `upper`.
+    fn validate_node(compare: &C, node: &Link<T, S>, lower: Option<&T>, upper: Option<&T>) -> Result<(i32, usize), InvariantViolation> {
+        let node = match node {
+            Some(node) => node,
+            None => return Ok((0, 0)),
+        };
+
+        let below_lower = lower.is_some_and(|lower| compare.compare(&node.value, lower) != Ordering::Greater);
+        let above_upper = upper.is_some_and(|upper| compare.compare(&node.value, upper) != Ordering::Less);
+        if below_lower || above_upper {
+            return Err(InvariantViolation::OutOfOrder);
+        }
+        let (left_height, left_size) = Self::validate_node(compare, &node.left, lower, Some(&node.value))?;
+        let (right_height, right_size) = Self::validate_node(compare, &node.right, Some(&node.value), upper)?;
+
+        if node.height != 1 + i32::max(left_height, right_height) {
+            return Err(InvariantViolation::HeightMismatch);
+        }
+        if (left_height - right_height).abs() > 1 {
+            return Err(InvariantViolation::Unbalanced);
+        }
+        if node.size != 1 + left_size + right_size {
+            return Err(InvariantViolation::SizeMismatch);
+        }
+        Ok((node.height, node.size))
+    }
+
     pub fn in_order_traversal(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        Self::in_order(&self.root, &mut result);
-        result
+        self.iter().collect()
+    }
+}
+
+impl<T, S: Summary<T>, C: Compare<T>> Join for AVLTree<T, S, C> {
+    type Node = TreeNode<T, S>;
+
+    fn join(left: Link<T, S>, mut mid: Box<TreeNode<T, S>>, right: Link<T, S>) -> Box<TreeNode<T, S>> {
+        let (left_height, right_height) = (Self::height(&left), Self::height(&right));
+        if left_height > right_height + 1 {
+            Self::join_right(left.unwrap(), mid, right)
+        } else if right_height > left_height + 1 {
+            Self::join_left(left, mid, right.unwrap())
+        } else {
+            mid.left = left;
+            mid.right = right;
+            Self::update(&mut mid);
+            mid
+        }
+    }
+
+    fn join_pair(left: Link<T, S>, right: Link<T, S>) -> Link<T, S> {
+        match left {
+            Some(left) => {
+                let (left, max) = Self::remove_max_node(left);
+                Some(Self::join(left, max, right))
+            }
+            None => right,
+        }
+    }
+}
+
+pub struct Range<'a, T, R, S = (), C = Natural> {
+    inner: binary_node::Range<'a, TreeNode<T, S>, R, C>,
+}
+
+impl<'a, T, R: RangeBounds<T>, S, C: Compare<T>> Iterator for Range<'a, T, R, S, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next()
+    }
+}
+
+pub struct Iter<'a, T, S = ()> {
+    front: Vec<&'a TreeNode<T, S>>,
+    back: Vec<&'a TreeNode<T, S>>,
+    len: usize,
+}
+
+impl<'a, T, S> Iter<'a, T, S> {
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<T, S>>) {
+        while let Some(current) = node {
+            self.front.push(current);
+            node = current.left.as_deref();
+        }
+    }
+
+    fn push_right(&mut self, mut node: Option<&'a TreeNode<T, S>>) {
+        while let Some(current) = node {
+            self.back.push(current);
+            node = current.right.as_deref();
+        }
+    }
+}
+
+impl<'a, T, S> Iterator for Iter<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, S> DoubleEndedIterator for Iter<'a, T, S> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.push_right(node.left.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+}
+
+impl<'a, T, S> ExactSizeIterator for Iter<'a, T, S> {}
+
+// An in-order walk that never descends into a subtree whose summary fails
+// the predicate given to `next_by`. Every call must pass the same predicate.
+pub(crate) struct Pruned<'a, T, S> {
+    stack: Vec<&'a TreeNode<T, S>>,
+    // The subtree to descend into before the stack is popped again.
+    pending: Option<&'a TreeNode<T, S>>,
+}
+
+impl<'a, T, S> Pruned<'a, T, S> {
+    pub(crate) fn next_by<P: Fn(&S) -> bool>(&mut self, enter: P) -> Option<&'a T> {
+        let mut node = self.pending.take();
+        while let Some(current) = node {
+            if !enter(&current.summary) {
+                break;
+            }
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+
+        let node = self.stack.pop()?;
+        self.pending = node.right.as_deref();
+        Some(&node.value)
+    }
+
+    // Ends the walk early.
+    pub(crate) fn finish(&mut self) {
+        self.stack.clear();
+        self.pending = None;
+    }
+}
+
+pub struct IntoIter<T, S = ()> {
+    inner: OwnedIter<TreeNode<T, S>>,
+}
+
+impl<T, S> Iterator for IntoIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for IntoIter<T, S> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, S> ExactSizeIterator for IntoIter<T, S> {}
+
+impl<T, S> BinaryNode for TreeNode<T, S> {
+    type Value = T;
+    type Summary = S;
+
+    fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn summary(&self) -> &S {
+        &self.summary
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+
+    fn take_children(&mut self) -> (Link<T, S>, Link<T, S>) {
+        (self.left.take(), self.right.take())
+    }
+}
+
+impl<T, S> Open for TreeNode<T, S> {
+    type Item = T;
+
+    fn open(self: Box<Self>, parts: &mut Vec<Part<Self>>) {
+        let TreeNode { value, left, right, .. } = *self;
+        parts.extend(left.map(Part::Node));
+        parts.push(Part::Value(value));
+        parts.extend(right.map(Part::Node));
+    }
+}
+
+pub struct PreOrder<'a, T, S = ()> {
+    stack: Vec<&'a TreeNode<T, S>>,
+}
+
+impl<'a, T, S> Iterator for PreOrder<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        Some(&node.value)
     }
+}
+
+pub struct PostOrder<'a, T, S = ()> {
+    // The flag records whether the node's children have already been pushed.
+    stack: Vec<(&'a TreeNode<T, S>, bool)>,
+}
+
+impl<'a, T, S> Iterator for PostOrder<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(&node.value);
+            }
+            self.stack.push((node, true));
+            self.stack.extend(node.right.as_deref().map(|right| (right, false)));
+            self.stack.extend(node.left.as_deref().map(|left| (left, false)));
+        }
+    }
+}
+
+pub struct LevelOrder<'a, T, S = ()> {
+    queue: VecDeque<&'a TreeNode<T, S>>,
+}
+
+impl<'a, T, S> Iterator for LevelOrder<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.left.as_deref());
+        self.queue.extend(node.right.as_deref());
+        Some(&node.value)
+    }
+}
+
+impl<'a, T, S: Summary<T>, C: Compare<T>> IntoIterator for &'a AVLTree<T, S, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, S>;
+
+    fn into_iter(self) -> Iter<'a, T, S> {
+        self.iter()
+    }
+}
+
+impl<T, S: Summary<T>, C: Compare<T>> IntoIterator for AVLTree<T, S, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T, S>;
 
-    fn in_order<'a>(node: &'a Option<Box<TreeNode<T>>>, result: &mut Vec<&'a T>) {
-        if let Some(node) = node {
-            Self::in_order(&node.left, result);
-            result.push(&node.value);
-            Self::in_order(&node.right, result);
+    fn into_iter(self) -> IntoIter<T, S> {
+        let len = self.len();
+        IntoIter {
+            inner: OwnedIter::new(self.root, len),
         }
     }
 }
@@ -0,0 +1,452 @@
+use std::ops::RangeBounds;
+
+use super::bounds;
+use super::invariant::InvariantViolation;
+
+// Nodes live in `nodes` and refer to each other by index, which lets every
+// leaf point at its right neighbour without shared ownership.
+#[derive(Debug, Clone)]
+pub struct BPlusTree<K, V> {
+    nodes: Vec<Node<K, V>>,
+    free: Vec<usize>,
+    root: usize,
+    t: usize,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    children: Vec<usize>,
+    next: Option<usize>,
+    leaf: bool,
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf() -> Self {
+        Node {
+            keys: vec![],
+            values: vec![],
+            children: vec![],
+            next: None,
+            leaf: true,
+        }
+    }
+}
+
+impl<K: Ord + Clone, V> BPlusTree<K, V> {
+    // Panics if `t` is less than 2.
+    pub fn new(t: usize) -> Self {
+        assert!(t >= 2, "B-tree minimum degree must be at least 2");
+        BPlusTree {
+            nodes: vec![Node::leaf()],
+            free: vec![],
+            root: 0,
+            t,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, index: usize) -> Node<K, V> {
+        self.free.push(index);
+        std::mem::replace(&mut self.nodes[index], Node::leaf())
+    }
+
+    // Child `i` of an internal node holds the keys in `[keys[i - 1], keys[i])`.
+    fn child_index(node: &Node<K, V>, key: &K) -> usize {
+        node.keys.partition_point(|separator| separator <= key)
+    }
+
+    fn find_leaf(&self, key: &K) -> usize {
+        let mut current = self.root;
+        while !self.nodes[current].leaf {
+            let node = &self.nodes[current];
+            current = node.children[Self::child_index(node, key)];
+        }
+        current
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let leaf = &self.nodes[self.find_leaf(key)];
+        leaf.keys.binary_search(key).ok().map(|pos| &leaf.values[pos])
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let leaf = self.find_leaf(key);
+        let leaf = &mut self.nodes[leaf];
+        leaf.keys.binary_search(key).ok().map(|pos| &mut leaf.values[pos])
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (previous, split) = self.insert_into(self.root, key, value);
+        if let Some((separator, right)) = split {
+            let root = self.alloc(Node {
+                keys: vec![separator],
+                values: vec![],
+                children: vec![self.root, right],
+                next: None,
+                leaf: false,
+            });
+            self.root = root;
+        }
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    // Inserts below `index`, returning the replaced value and, if the node
+    // overflowed, the separator and index of its new right sibling.
+    fn insert_into(&mut self, index: usize, key: K, value: V) -> (Option<V>, Option<(K, usize)>) {
+        if self.nodes[index].leaf {
+            let node = &mut self.nodes[index];
+            match node.keys.binary_search(&key) {
+                Ok(pos) => return (Some(std::mem::replace(&mut node.values[pos], value)), None),
+                Err(pos) => {
+                    node.keys.insert(pos, key);
+                    node.values.insert(pos, value);
+                }
+            }
+        } else {
+            let pos = Self::child_index(&self.nodes[index], &key);
+            let (previous, split) = self.insert_into(self.nodes[index].children[pos], key, value);
+            match split {
+                Some((separator, right)) => {
+                    let node = &mut self.nodes[index];
+                    node.keys.insert(pos, separator);
+                    node.children.insert(pos + 1, right);
+                }
+                None => return (previous, None),
+            }
+        }
+
+        if self.nodes[index].keys.len() < 2 * self.t {
+            return (None, None);
+        }
+        (None, Some(self.split(index)))
+    }
+
+    fn split(&mut self, index: usize) -> (K, usize) {
+        let t = self.t;
+        let node = &mut self.nodes[index];
+        if node.leaf {
+            let right = Node {
+                keys: node.keys.split_off(t),
+                values: node.values.split_off(t),
+                children: vec![],
+                next: node.next,
+                leaf: true,
+            };
+            let separator = right.keys[0].clone();
+            let right = self.alloc(right);
+            self.nodes[index].next = Some(right);
+            (separator, right)
+        } else {
+            let keys = node.keys.split_off(t + 1);
+            let children = node.children.split_off(t + 1);
+            let separator = node.keys.pop().unwrap();
+            let right = self.alloc(Node {
+                keys,
+                values: vec![],
+                children,
+                next: None,
+                leaf: false,
+            });
+            (separator, right)
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = self.remove_from(self.root, key)?;
+
+        // Shrink the tree when the root has been emptied by a merge.
+        let root = &self.nodes[self.root];
+        if !root.leaf && root.keys.is_empty() {
+            let child = root.children[0];
+            self.release(self.root);
+            self.root = child;
+        }
+        self.len -= 1;
+        Some(removed)
+    }
+
+    fn remove_from(&mut self, index: usize, key: &K) -> Option<V> {
+        if self.nodes[index].leaf {
+            let node = &mut self.nodes[index];
+            let pos = node.keys.binary_search(key).ok()?;
+            node.keys.remove(pos);
+            return Some(node.values.remove(pos));
+        }
+
+        let pos = Self::child_index(&self.nodes[index], key);
+        let removed = self.remove_from(self.nodes[index].children[pos], key)?;
+        if self.nodes[self.nodes[index].children[pos]].keys.len() < self.t - 1 {
+            self.fill_child(index, pos);
+        }
+        Some(removed)
+    }
+
+    // Restores the minimum key count of the child at `pos` by borrowing from
+    // a sibling that can spare a key, or by merging with one.
+    fn fill_child(&mut self, index: usize, pos: usize) {
+        let t = self.t;
+        let children = &self.nodes[index].children;
+        if pos > 0 && self.nodes[children[pos - 1]].keys.len() >= t {
+            self.borrow_from_prev(index, pos);
+        } else if pos + 1 < children.len() && self.nodes[children[pos + 1]].keys.len() >= t {
+            self.borrow_from_next(index, pos);
+        } else if pos + 1 < children.len() {
+            self.merge_children(index, pos);
+        } else {
+            self.merge_children(index, pos - 1);
+        }
+    }
+
+    fn borrow_from_prev(&mut self, index: usize, pos: usize) {
+        let (sibling, child) = (self.nodes[index].children[pos - 1], self.nodes[index].children[pos]);
+        let key = self.nodes[sibling].keys.pop().unwrap();
+        if self.nodes[child].leaf {
+            let value = self.nodes[sibling].values.pop().unwrap();
+            self.nodes[index].keys[pos - 1] = key.clone();
+            self.nodes[child].keys.insert(0, key);
+            self.nodes[child].values.insert(0, value);
+        } else {
+            let grandchild = self.nodes[sibling].children.pop().unwrap();
+            let separator = std::mem::replace(&mut self.nodes[index].keys[pos - 1], key);
+            self.nodes[child].keys.insert(0, separator);
+            self.nodes[child].children.insert(0, grandchild);
+        }
+    }
+
+    fn borrow_from_next(&mut self, index: usize, pos: usize) {
+        let (child, sibling) = (self.nodes[index].children[pos], self.nodes[index].children[pos + 1]);
+        let key = self.nodes[sibling].keys.remove(0);
+        if self.nodes[child].leaf {
+            let value = self.nodes[sibling].values.remove(0);
+            self.nodes[index].keys[pos] = self.nodes[sibling].keys[0].clone();
+            self.nodes[child].keys.push(key);
+            self.nodes[child].values.push(value);
+        } else {
+            let grandchild = self.nodes[sibling].children.remove(0);
+            let separator = std::mem::replace(&mut self.nodes[index].keys[pos], key);
+            self.nodes[child].keys.push(separator);
+            self.nodes[child].children.push(grandchild);
+        }
+    }
+
+    fn merge_children(&mut self, index: usize, pos: usize) {
+        let separator = self.nodes[index].keys.remove(pos);
+        let sibling = self.nodes[index].children.remove(pos + 1);
+        let child = self.nodes[index].children[pos];
+        let sibling = self.release(sibling);
+
+        let child = &mut self.nodes[child];
+        if child.leaf {
+            child.next = sibling.next;
+        } else {
+            child.keys.push(separator);
+        }
+        child.keys.extend(sibling.keys);
+        child.values.extend(sibling.values);
+        child.children.extend(sibling.children);
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        let mut current = self.root;
+        while !self.nodes[current].leaf {
+            let node = &self.nodes[current];
+            let pos = node.keys.partition_point(|key| !bounds::above_start(range.start_bound(), key));
+            current = node.children[pos];
+        }
+        let pos = self.nodes[current]
+            .keys
+            .partition_point(|key| !bounds::above_start(range.start_bound(), key));
+        Range {
+            nodes: &self.nodes,
+            leaf: Some(current),
+            pos,
+            range,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut current = self.root;
+        while !self.nodes[current].leaf {
+            current = self.nodes[current].children[0];
+        }
+        Iter {
+            nodes: &self.nodes,
+            leaf: current,
+            pos: 0,
+            len: self.len,
+        }
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<(&K, &V)> {
+        self.iter().collect()
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        self.validate_node(self.root, None, None)?;
+
+        // The leaf chain alone must reach every entry, in order.
+        let mut count = 0;
+        let mut previous: Option<&K> = None;
+        let mut current = self.root;
+        while !self.nodes[current].leaf {
+            current = self.nodes[current].children[0];
+        }
+        let mut leaf = Some(current);
+        while let Some(index) = leaf {
+            for key in &self.nodes[index].keys {
+                if previous.is_some_and(|previous| previous >= key) {
+                    return Err(InvariantViolation::OutOfOrder);
+                }
+                previous = Some(key);
+                count += 1;
+            }
+            leaf = self.nodes[index].next;
+        }
+        if count != self.len {
+            return Err(InvariantViolation::LengthMismatch);
+        }
+        Ok(())
+    }
+
+    // Returns the leaf depth of a valid subtree whose keys lie in
+    // `[lower, upper)`.
+    fn validate_node(&self, index: usize, lower: Option<&K>, upper: Option<&K>) -> Result<usize, InvariantViolation> {
+        let node = &self.nodes[index];
+        let min_keys = match (index == self.root, node.leaf) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, _) => self.t - 1,
+        };
+        if node.keys.len() < min_keys || node.keys.len() > 2 * self.t - 1 {
+            return Err(InvariantViolation::KeyCount);
+        }
+        if node.keys.windows(2).any(|pair| pair[0] >= pair[1])
+            || node.keys.first().is_some_and(|first| lower.is_some_and(|lower| first < lower))
+            || node.keys.last().is_some_and(|last| upper.is_some_and(|upper| last >= upper))
+        {
+            return Err(InvariantViolation::OutOfOrder);
+        }
+
+        if node.leaf {
+            if node.values.len() != node.keys.len() || !node.children.is_empty() {
+                return Err(InvariantViolation::ChildCount);
+            }
+            return Ok(0);
+        }
+        if node.children.len() != node.keys.len() + 1 || !node.values.is_empty() {
+            return Err(InvariantViolation::ChildCount);
+        }
+
+        let mut depth = None;
+        for (i, &child) in node.children.iter().enumerate() {
+            let lower = if i == 0 { lower } else { Some(&node.keys[i - 1]) };
+            let upper = if i == node.keys.len() { upper } else { Some(&node.keys[i]) };
+            let child_depth = self.validate_node(child, lower, upper)?;
+            if depth.is_some_and(|depth| depth != child_depth) {
+                return Err(InvariantViolation::LeafDepthMismatch);
+            }
+            depth = Some(child_depth);
+        }
+        Ok(depth.unwrap() + 1)
+    }
+}
+
+pub struct Range<'a, K, V, R> {
+    nodes: &'a [Node<K, V>],
+    leaf: Option<usize>,
+    pos: usize,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            let node = &self.nodes[self.leaf?];
+            if self.pos == node.keys.len() {
+                self.leaf = node.next;
+                self.pos = 0;
+                continue;
+            }
+            let key = &node.keys[self.pos];
+            if !bounds::below_end(self.range.end_bound(), key) {
+                self.leaf = None;
+                return None;
+            }
+            self.pos += 1;
+            return Some((key, &node.values[self.pos - 1]));
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    leaf: usize,
+    pos: usize,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.pos == self.nodes[self.leaf].keys.len() {
+            self.leaf = self.nodes[self.leaf].next?;
+            self.pos = 0;
+        }
+        let node = &self.nodes[self.leaf];
+        self.pos += 1;
+        self.len -= 1;
+        Some((&node.keys[self.pos - 1], &node.values[self.pos - 1]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: Ord + Clone, V> IntoIterator for &'a BPlusTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
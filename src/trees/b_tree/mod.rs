@@ -0,0 +1,1091 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::ops::RangeBounds;
+
+use super::bounds;
+use super::invariant::InvariantViolation;
+use super::owned_iter::{Open, OwnedIter, Part};
+use crate::utils::compare::{Compare, Natural};
+
+mod pager;
+pub mod persistent;
+
+#[derive(Debug, Clone)]
+pub struct BTree<T, C = Natural> {
+    root: Option<Box<Node<T>>>,
+    t: usize,
+    len: usize,
+    compare: C,
+}
+
+// The separator and new right sibling produced by splitting a node.
+type Split<T> = (T, Box<Node<T>>);
+
+// A subtree and its height, which is 0 for an empty tree and 1 for a leaf.
+type Tree<T> = (Option<Box<Node<T>>>, usize);
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    keys: Vec<T>,
+    children: Vec<Option<Box<Node<T>>>>,
+    leaf: bool,
+}
+
+impl<T: Ord> BTree<T> {
+    pub fn new(t: usize) -> Self {
+        BTree::with_comparator(t, Natural)
+    }
+
+    // Builds the tree bottom-up from keys in ascending order, packing each
+    // node to `fill_factor` of its capacity where the minimum occupancy
+    // allows it. Repeated keys are kept once. Panics if the keys are out of
+    // order, `t` is less than 2 or `fill_factor` is not in (0, 1].
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(t: usize, keys: I, fill_factor: f64) -> Self {
+        assert!(t >= 2, "B-tree minimum degree must be at least 2");
+        assert!(fill_factor > 0.0 && fill_factor <= 1.0, "fill factor must be in (0, 1]");
+        let mut keys: Vec<T> = keys.into_iter().collect();
+        keys.dedup();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]), "from_sorted_iter requires keys in ascending order");
+        BTree::build(t, keys, fill_factor, Natural)
+    }
+}
+
+impl<T, C: Compare<T>> BTree<T, C> {
+    // Creates an empty tree ordered by `compare` instead of `Ord`. Panics if
+    // `t` is less than 2.
+    pub fn with_comparator(t: usize, compare: C) -> Self {
+        assert!(t >= 2, "B-tree minimum degree must be at least 2");
+        BTree {
+            root: None,
+            t,
+            len: 0,
+            compare,
+        }
+    }
+
+    // Bulk loads distinct keys that are already in ascending order.
+    fn build(t: usize, mut keys: Vec<T>, fill_factor: f64, compare: C) -> Self {
+        let len = keys.len();
+        if len == 0 {
+            return BTree::with_comparator(t, compare);
+        }
+
+        let max_keys = 2 * t - 1;
+        let per_node = ((fill_factor * max_keys as f64).round() as usize).clamp(t - 1, max_keys);
+        let mut children: Vec<Option<Box<Node<T>>>> = Vec::new();
+        // Each pass packs one level, pushing the separators between its nodes
+        // up as the keys of the next level.
+        let root = loop {
+            let n = keys.len();
+            let count = (n + 1).div_ceil(per_node + 1).min((n + 1) / t).max(1);
+            let (base, extra) = ((n + 1) / count - 1, (n + 1) % count);
+            let leaf = children.is_empty();
+
+            let mut keys_iter = keys.into_iter();
+            let mut children_iter = children.into_iter();
+            let mut separators = Vec::with_capacity(count - 1);
+            let mut level = Vec::with_capacity(count);
+            for i in 0..count {
+                let size = base + usize::from(i < extra);
+                level.push(Some(Box::new(Node {
+                    keys: keys_iter.by_ref().take(size).collect(),
+                    children: children_iter.by_ref().take(if leaf { 0 } else { size + 1 }).collect(),
+                    leaf,
+                })));
+                if i + 1 < count {
+                    separators.push(keys_iter.next().unwrap());
+                }
+            }
+
+            if count == 1 {
+                break level.pop().unwrap();
+            }
+            keys = separators;
+            children = level;
+        };
+
+        BTree {
+            root,
+            t,
+            len,
+            compare,
+        }
+    }
+
+    pub fn insert(&mut self, key: T) {
+        if let Err(path) = self.search_by(|other| self.compare.compare(&key, other)) {
+            self.insert_at(&path, key);
+        }
+    }
+
+    // Inserts `key` at the vacant position `path` that `search_by` reported,
+    // splitting overfull nodes on the way back up, and returns the path at
+    // which the key ended up.
+    pub(crate) fn insert_at(&mut self, path: &[usize], key: T) -> Vec<usize> {
+        self.len += 1;
+        let mut root = match self.root.take() {
+            Some(root) => root,
+            None => {
+                self.root = Some(Box::new(Node {
+                    keys: vec![key],
+                    children: vec![],
+                    leaf: true,
+                }));
+                return vec![0];
+            }
+        };
+
+        let (mut location, split) = self.insert_into(&mut root, path, key);
+        self.root = Some(match split {
+            Some((median, right)) => {
+                self.lift(&mut location, 0, true);
+                Box::new(Node {
+                    keys: vec![median],
+                    children: vec![Some(root), Some(right)],
+                    leaf: false,
+                })
+            }
+            None => root,
+        });
+        location.reverse();
+        location
+    }
+
+    // Inserts `key` below `node` and returns where it landed, innermost
+    // index first, along with the separator and right half if `node`
+    // overflowed and had to be split.
+    fn insert_into(&self, node: &mut Node<T>, path: &[usize], key: T) -> (Vec<usize>, Option<Split<T>>) {
+        let location = if node.leaf {
+            node.keys.insert(path[0], key);
+            vec![path[0]]
+        } else {
+            let pos = path[0];
+            let (mut location, split) = self.insert_into(node.children[pos].as_mut().unwrap(), &path[1..], key);
+            let lifted = split.is_some();
+            if let Some((median, right)) = split {
+                node.keys.insert(pos, median);
+                node.children.insert(pos + 1, Some(right));
+            }
+            self.lift(&mut location, pos, lifted);
+            location
+        };
+
+        (location, self.split_full(node))
+    }
+
+    // Rewrites a location within the child at `pos` into one within its
+    // parent. A split child keeps its first t - 1 keys and t children, gives
+    // key t - 1 to the parent, and moves the rest to a new sibling at
+    // `pos + 1`.
+    fn lift(&self, location: &mut Vec<usize>, pos: usize, split: bool) {
+        let t = self.t;
+        let in_child = location.len() == 1;
+        let top = location.last_mut().unwrap();
+        if !split || *top < t - 1 || (*top == t - 1 && !in_child) {
+            location.push(pos);
+        } else if *top == t - 1 {
+            *top = pos;
+        } else {
+            *top -= t;
+            location.push(pos + 1);
+        }
+    }
+
+    fn find<F: FnMut(&T) -> Ordering>(keys: &[T], probe: &mut F) -> Result<usize, usize> {
+        keys.binary_search_by(|key| probe(key).reverse())
+    }
+
+    pub fn remove(&mut self, key: &T) -> bool {
+        let mut root = self.root.take();
+        let removed = self.remove_in(&mut root, &mut |other: &T| self.compare.compare(key, other));
+        self.root = root;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed.is_some()
+    }
+
+    // Removes the key `probe` reports as equal. `probe` orders the target
+    // relative to the stored key it is given, like `search_by`.
+    pub(crate) fn remove_by<F: FnMut(&T) -> Ordering>(&mut self, mut probe: F) -> Option<T> {
+        let mut root = self.root.take();
+        let removed = self.remove_in(&mut root, &mut probe);
+        self.root = root;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_in<F: FnMut(&T) -> Ordering>(&self, root: &mut Option<Box<Node<T>>>, probe: &mut F) -> Option<T> {
+        let mut node = root.take()?;
+        let removed = self.remove_from(&mut node, probe);
+
+        // Shrink the tree when the root has been emptied by a merge.
+        if node.keys.is_empty() {
+            *root = if node.leaf { None } else { node.children.pop().unwrap() };
+        } else {
+            *root = Some(node);
+        }
+        removed
+    }
+
+    fn remove_from<F: FnMut(&T) -> Ordering>(&self, node: &mut Node<T>, probe: &mut F) -> Option<T> {
+        let t = self.t;
+        match Self::find(&node.keys, probe) {
+            Ok(pos) if node.leaf => Some(node.keys.remove(pos)),
+            Ok(pos) => {
+                if node.children[pos].as_ref().unwrap().keys.len() >= t {
+                    let predecessor = self.remove_max(node.children[pos].as_mut().unwrap());
+                    Some(std::mem::replace(&mut node.keys[pos], predecessor))
+                } else if node.children[pos + 1].as_ref().unwrap().keys.len() >= t {
+                    let successor = self.remove_min(node.children[pos + 1].as_mut().unwrap());
+                    Some(std::mem::replace(&mut node.keys[pos], successor))
+                } else {
+                    self.merge_children(node, pos);
+                    self.remove_from(node.children[pos].as_mut().unwrap(), probe)
+                }
+            }
+            Err(_) if node.leaf => None,
+            Err(pos) => {
+                let pos = self.fill_child(node, pos);
+                self.remove_from(node.children[pos].as_mut().unwrap(), probe)
+            }
+        }
+    }
+
+    fn remove_max(&self, node: &mut Node<T>) -> T {
+        if node.leaf {
+            return node.keys.pop().unwrap();
+        }
+        let pos = self.fill_child(node, node.keys.len());
+        self.remove_max(node.children[pos].as_mut().unwrap())
+    }
+
+    fn remove_min(&self, node: &mut Node<T>) -> T {
+        if node.leaf {
+            return node.keys.remove(0);
+        }
+        let pos = self.fill_child(node, 0);
+        self.remove_min(node.children[pos].as_mut().unwrap())
+    }
+
+    // Ensures the child at `pos` holds at least `t` keys before descending
+    // into it, borrowing from a sibling or merging with one. Returns the
+    // index of the child that now covers the original key range.
+    fn fill_child(&self, node: &mut Node<T>, pos: usize) -> usize {
+        let t = self.t;
+        if node.children[pos].as_ref().unwrap().keys.len() >= t {
+            return pos;
+        }
+
+        if pos > 0 && node.children[pos - 1].as_ref().unwrap().keys.len() >= t {
+            self.borrow_from_prev(node, pos);
+            pos
+        } else if pos < node.keys.len() && node.children[pos + 1].as_ref().unwrap().keys.len() >= t {
+            self.borrow_from_next(node, pos);
+            pos
+        } else if pos < node.keys.len() {
+            self.merge_children(node, pos);
+            pos
+        } else {
+            self.merge_children(node, pos - 1);
+            pos - 1
+        }
+    }
+
+    fn borrow_from_prev(&self, node: &mut Node<T>, pos: usize) {
+        let mut sibling = node.children[pos - 1].take().unwrap();
+        let child = node.children[pos].as_mut().unwrap();
+
+        let key = std::mem::replace(&mut node.keys[pos - 1], sibling.keys.pop().unwrap());
+        child.keys.insert(0, key);
+        if !child.leaf {
+            child.children.insert(0, sibling.children.pop().unwrap());
+        }
+
+        node.children[pos - 1] = Some(sibling);
+    }
+
+    fn borrow_from_next(&self, node: &mut Node<T>, pos: usize) {
+        let mut sibling = node.children[pos + 1].take().unwrap();
+        let child = node.children[pos].as_mut().unwrap();
+
+        let key = std::mem::replace(&mut node.keys[pos], sibling.keys.remove(0));
+        child.keys.push(key);
+        if !child.leaf {
+            child.children.push(sibling.children.remove(0));
+        }
+
+        node.children[pos + 1] = Some(sibling);
+    }
+
+    fn merge_children(&self, node: &mut Node<T>, pos: usize) {
+        let sibling = node.children.remove(pos + 1).unwrap();
+        let child = node.children[pos].as_mut().unwrap();
+
+        child.keys.push(node.keys.remove(pos));
+        child.keys.extend(sibling.keys);
+        child.children.extend(sibling.children);
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        self.combine(other, |in_self, in_other| in_self || in_other)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        self.combine(other, |in_self, in_other| in_self && in_other)
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        self.combine(other, |in_self, in_other| in_self && !in_other)
+    }
+
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        self.combine(other, |in_self, in_other| in_self != in_other)
+    }
+
+    // Set operations use the same split and join scheme as the binary trees:
+    // the keys of a root in `self` split `other` into pieces, each piece is
+    // combined with the child between the same keys, and the results are
+    // joined back up around the keys that `keep` selects. Subtrees facing an
+    // empty piece are kept or dropped whole, so only the nodes on the paths
+    // to the smaller tree's keys are visited rather than every node of both.
+    fn combine<F: Fn(bool, bool) -> bool>(mut self, mut other: Self, keep: F) -> Self {
+        if other.t != self.t {
+            // Joining needs both trees to use the same node bounds.
+            let BTree { root, len, compare, .. } = other;
+            other = BTree::build(self.t, OwnedIter::new(root, len).collect(), 1.0, compare);
+        }
+
+        let (self_len, other_len) = (self.len, other.len);
+        let (self_height, other_height) = (self.height(), other.height());
+        let (a, b) = ((self.root.take(), self_height), (other.root.take(), other_height));
+        let mut common = 0;
+        let (root, _) = self.combine_trees(a, b, &keep, &mut common);
+        self.root = root;
+        self.len = [(keep(true, false), self_len - common), (keep(false, true), other_len - common), (keep(true, true), common)]
+            .iter()
+            .filter(|(kept, _)| *kept)
+            .map(|(_, count)| count)
+            .sum();
+        self
+    }
+
+    // Combines two trees under `keep`, counting the keys found in both.
+    fn combine_trees<F: Fn(bool, bool) -> bool>(&self, a: Tree<T>, b: Tree<T>, keep: &F, common: &mut usize) -> Tree<T> {
+        if a.0.is_none() {
+            return if keep(false, true) { b } else { (None, 0) };
+        }
+        if b.0.is_none() {
+            return if keep(true, false) { a } else { (None, 0) };
+        }
+
+        let (node, height) = a;
+        let Node { keys, children, leaf } = *node.unwrap();
+        let children = Self::subtrees(children, leaf, keys.len(), height);
+        let mut pieces = Vec::with_capacity(children.len());
+        let mut kept = Vec::with_capacity(keys.len());
+        let mut rest = b;
+        for key in keys {
+            let (less, found, greater) = self.split(rest, &key);
+            *common += usize::from(found.is_some());
+            pieces.push(less);
+            kept.push(keep(true, found.is_some()).then_some(key));
+            rest = greater;
+        }
+        pieces.push(rest);
+
+        let mut results = children.into_iter().zip(pieces).map(|(child, piece)| self.combine_trees(child, piece, keep, common));
+        let mut tree = results.next().unwrap();
+        for (key, right) in kept.into_iter().zip(results) {
+            tree = match key {
+                Some(key) => self.join(tree, key, right),
+                None => self.join_pair(tree, right),
+            };
+        }
+        tree
+    }
+
+    // Splits a tree into the keys ordered before and after `key`, returning
+    // the key itself separately if the tree holds it.
+    fn split(&self, tree: Tree<T>, key: &T) -> (Tree<T>, Option<T>, Tree<T>) {
+        let (node, height) = match tree {
+            (Some(node), height) => (node, height),
+            (None, _) => return ((None, 0), None, (None, 0)),
+        };
+
+        let Node { mut keys, children, leaf } = *node;
+        let mut children = Self::subtrees(children, leaf, keys.len(), height);
+        match Self::find(&keys, &mut |other: &T| self.compare.compare(key, other)) {
+            Ok(pos) => {
+                let right_keys = keys.split_off(pos + 1);
+                let found = keys.pop();
+                let right_children = children.split_off(pos + 1);
+                (self.join_all(keys, children), found, self.join_all(right_keys, right_children))
+            }
+            Err(pos) => {
+                let right_keys = keys.split_off(pos);
+                let mut right_children = children.split_off(pos + 1);
+                let (less, found, greater) = self.split(children.pop().unwrap(), key);
+                children.push(less);
+                right_children.insert(0, greater);
+                (self.join_all(keys, children), found, self.join_all(right_keys, right_children))
+            }
+        }
+    }
+
+    // The children of a node at `height` as trees, with empty trees standing
+    // in for the children a leaf does not have.
+    fn subtrees(children: Vec<Option<Box<Node<T>>>>, leaf: bool, keys: usize, height: usize) -> Vec<Tree<T>> {
+        if leaf {
+            (0..=keys).map(|_| (None, 0)).collect()
+        } else {
+            children.into_iter().map(|child| (child, height - 1)).collect()
+        }
+    }
+
+    // Joins `trees` back together with `keys` between them.
+    fn join_all(&self, keys: Vec<T>, mut trees: Vec<Tree<T>>) -> Tree<T> {
+        let mut result = trees.pop().unwrap();
+        for (key, tree) in keys.into_iter().zip(trees).rev() {
+            result = self.join(tree, key, result);
+        }
+        result
+    }
+
+    // Joins two trees around a key, where every key in `left` orders before
+    // `mid` and every key in `right` after it. The shorter tree is hung off
+    // the matching edge of the taller one, so this takes time proportional
+    // to the difference in height.
+    fn join(&self, left: Tree<T>, mid: T, right: Tree<T>) -> Tree<T> {
+        let ((left, left_height), (right, right_height)) = (left, right);
+        match left_height.cmp(&right_height) {
+            Ordering::Greater => {
+                let mut root = left.unwrap();
+                let split = self.join_right(&mut root, left_height, mid, right, right_height);
+                Self::grow(root, left_height, split)
+            }
+            Ordering::Less => {
+                let mut root = right.unwrap();
+                let split = self.join_left(&mut root, right_height, left, left_height, mid);
+                Self::grow(root, right_height, split)
+            }
+            Ordering::Equal => match (left, right) {
+                (Some(left), Some(right)) => {
+                    let mut root = Node {
+                        keys: vec![mid],
+                        children: vec![Some(left), Some(right)],
+                        leaf: false,
+                    };
+                    self.rebalance(&mut root, 0);
+                    if root.keys.is_empty() {
+                        (root.children.pop().unwrap(), left_height)
+                    } else {
+                        (Some(Box::new(root)), left_height + 1)
+                    }
+                }
+                _ => {
+                    let leaf = Node {
+                        keys: vec![mid],
+                        children: vec![],
+                        leaf: true,
+                    };
+                    (Some(Box::new(leaf)), 1)
+                }
+            },
+        }
+    }
+
+    // Adds `mid` and the shorter tree `right` at the right edge of `node`,
+    // splitting the nodes that overflow on the way back up.
+    fn join_right(&self, node: &mut Node<T>, height: usize, mid: T, right: Option<Box<Node<T>>>, right_height: usize) -> Option<Split<T>> {
+        if height == right_height + 1 {
+            node.keys.push(mid);
+            if let Some(right) = right {
+                node.children.push(Some(right));
+                self.rebalance(node, node.keys.len() - 1);
+            }
+        } else {
+            let last = node.children.len() - 1;
+            if let Some((median, sibling)) = self.join_right(node.children[last].as_mut().unwrap(), height - 1, mid, right, right_height) {
+                node.keys.push(median);
+                node.children.push(Some(sibling));
+            }
+        }
+        self.split_full(node)
+    }
+
+    // Adds the shorter tree `left` and `mid` at the left edge of `node`.
+    fn join_left(&self, node: &mut Node<T>, height: usize, left: Option<Box<Node<T>>>, left_height: usize, mid: T) -> Option<Split<T>> {
+        if height == left_height + 1 {
+            node.keys.insert(0, mid);
+            if let Some(left) = left {
+                node.children.insert(0, Some(left));
+                self.rebalance(node, 0);
+            }
+        } else {
+            if let Some((median, sibling)) = self.join_left(node.children[0].as_mut().unwrap(), height - 1, left, left_height, mid) {
+                node.keys.insert(0, median);
+                node.children.insert(1, Some(sibling));
+            }
+        }
+        self.split_full(node)
+    }
+
+    // Joins two trees where every key in `left` orders before every key in
+    // `right`.
+    fn join_pair(&self, left: Tree<T>, right: Tree<T>) -> Tree<T> {
+        let (mut root, height) = match left {
+            (Some(root), height) => (root, height),
+            (None, _) => return right,
+        };
+        let max = self.remove_max(&mut root);
+        let left = if !root.keys.is_empty() {
+            (Some(root), height)
+        } else if root.leaf {
+            (None, 0)
+        } else {
+            (root.children.pop().unwrap(), height - 1)
+        };
+        self.join(left, max, right)
+    }
+
+    // Puts a new root above `root` if joining split it.
+    fn grow(root: Box<Node<T>>, height: usize, split: Option<Split<T>>) -> Tree<T> {
+        match split {
+            Some((median, right)) => {
+                let root = Node {
+                    keys: vec![median],
+                    children: vec![Some(root), Some(right)],
+                    leaf: false,
+                };
+                (Some(Box::new(root)), height + 1)
+            }
+            None => (Some(root), height),
+        }
+    }
+
+    // Brings the children at `pos` and `pos + 1` back to at least t - 1 keys
+    // each when a join has left one short: merges them if their keys fit in
+    // one node, and otherwise shares the keys out evenly.
+    fn rebalance(&self, node: &mut Node<T>, pos: usize) {
+        let t = self.t;
+        let left_len = node.children[pos].as_ref().unwrap().keys.len();
+        let right_len = node.children[pos + 1].as_ref().unwrap().keys.len();
+        if left_len >= t - 1 && right_len >= t - 1 {
+            return;
+        }
+        if left_len + right_len < 2 * t - 1 {
+            self.merge_children(node, pos);
+            return;
+        }
+
+        let mut right = node.children[pos + 1].take().unwrap();
+        let left = node.children[pos].as_mut().unwrap();
+        left.keys.push(node.keys.remove(pos));
+        left.keys.append(&mut right.keys);
+        left.children.append(&mut right.children);
+
+        let half = (left.keys.len() - 1) / 2;
+        right.keys = left.keys.split_off(half + 1);
+        node.keys.insert(pos, left.keys.pop().unwrap());
+        if !left.leaf {
+            right.children = left.children.split_off(half + 1);
+        }
+        node.children[pos + 1] = Some(right);
+    }
+
+    // Splits `node` once it has overflowed to 2t keys, returning the
+    // separator and the new right sibling.
+    fn split_full(&self, node: &mut Node<T>) -> Option<Split<T>> {
+        if node.keys.len() < 2 * self.t {
+            return None;
+        }
+        let (median, keys, children) = split_keys(&mut node.keys, &mut node.children, self.t);
+        Some((median, Box::new(Node { keys, children, leaf: node.leaf })))
+    }
+
+    fn height(&self) -> usize {
+        let mut height = 0;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            height += 1;
+            current = if node.leaf { None } else { node.children[0].as_deref() };
+        }
+        height
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, key: &T) -> bool {
+        self.get_by(|other| self.compare.compare(key, other)).is_some()
+    }
+
+    // Finds the path to the key `probe` reports as equal: the child index
+    // taken at each level and then the key's index in its node. Otherwise
+    // returns the path to the leaf slot where a new key would go. `probe`
+    // orders the target relative to the stored key it is given, so callers
+    // can search by part of a key.
+    pub(crate) fn search_by<F: FnMut(&T) -> Ordering>(&self, mut probe: F) -> Result<Vec<usize>, Vec<usize>> {
+        let mut path = Vec::new();
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            match Self::find(&node.keys, &mut probe) {
+                Ok(pos) => {
+                    path.push(pos);
+                    return Ok(path);
+                }
+                Err(pos) => {
+                    path.push(pos);
+                    current = if node.leaf { None } else { node.children[pos].as_deref() };
+                }
+            }
+        }
+        if path.is_empty() {
+            path.push(0);
+        }
+        Err(path)
+    }
+
+    pub(crate) fn get_by<F: FnMut(&T) -> Ordering>(&self, mut probe: F) -> Option<&T> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            match Self::find(&node.keys, &mut probe) {
+                Ok(pos) => return Some(&node.keys[pos]),
+                Err(pos) => current = if node.leaf { None } else { node.children[pos].as_deref() },
+            }
+        }
+        None
+    }
+
+    // Callers must not change how the key orders.
+    pub(crate) fn get_by_mut<F: FnMut(&T) -> Ordering>(&mut self, mut probe: F) -> Option<&mut T> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            match Self::find(&node.keys, &mut probe) {
+                Ok(pos) => return Some(&mut node.keys[pos]),
+                Err(pos) => current = if node.leaf { None } else { node.children[pos].as_deref_mut() },
+            }
+        }
+        None
+    }
+
+    // Returns the key at a path `search_by` or `insert_at` produced. Callers
+    // must not change how the key orders.
+    pub(crate) fn get_at_mut(&mut self, path: &[usize]) -> &mut T {
+        let (&pos, children) = path.split_last().unwrap();
+        let mut node = self.root.as_deref_mut().unwrap();
+        for &child in children {
+            node = node.children[child].as_deref_mut().unwrap();
+        }
+        &mut node.keys[pos]
+    }
+
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T, R, C> {
+        let mut stack = Vec::new();
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let pos = node.keys.partition_point(|key| !bounds::above_start_by(&self.compare, range.start_bound(), key));
+            stack.push((node, pos));
+            current = if node.leaf { None } else { node.children[pos].as_deref() };
+        }
+        Range {
+            stack,
+            range,
+            compare: &self.compare,
+        }
+    }
+
+    pub fn floor(&self, key: &T) -> Option<&T> {
+        self.descend(|k| self.compare.compare(k, key) != Ordering::Greater, true)
+    }
+
+    pub fn ceiling(&self, key: &T) -> Option<&T> {
+        self.descend(|k| self.compare.compare(k, key) == Ordering::Less, false)
+    }
+
+    pub fn predecessor(&self, key: &T) -> Option<&T> {
+        self.descend(|k| self.compare.compare(k, key) == Ordering::Less, true)
+    }
+
+    pub fn successor(&self, key: &T) -> Option<&T> {
+        self.descend(|k| self.compare.compare(k, key) != Ordering::Greater, false)
+    }
+
+    // Walks down the tree partitioning every node's keys with `pred` and
+    // returns the closest key on the left (`below`) or right of the split.
+    fn descend<P: Fn(&T) -> bool>(&self, pred: P, below: bool) -> Option<&T> {
+        let mut current = self.root.as_deref();
+        let mut best = None;
+        while let Some(node) = current {
+            let pos = node.keys.partition_point(&pred);
+            if below && pos > 0 {
+                best = Some(&node.keys[pos - 1]);
+            } else if !below && pos < node.keys.len() {
+                best = Some(&node.keys[pos]);
+            }
+            current = if node.leaf { None } else { node.children[pos].as_deref() };
+        }
+        best
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            len: self.len,
+        };
+        iter.push_left(self.root.as_deref());
+        iter.push_right(self.root.as_deref());
+        iter
+    }
+
+    pub fn pre_order(&self) -> PreOrder<'_, T> {
+        PreOrder {
+            stack: self.root.as_deref().into_iter().collect(),
+            keys: [].iter(),
+        }
+    }
+
+    pub fn post_order(&self) -> PostOrder<'_, T> {
+        PostOrder {
+            stack: self.root.as_deref().map(|node| (node, false)).into_iter().collect(),
+            keys: [].iter(),
+        }
+    }
+
+    pub fn level_order(&self) -> LevelOrder<'_, T> {
+        LevelOrder {
+            queue: self.root.as_deref().into_iter().collect(),
+            keys: [].iter(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        let count = match &self.root {
+            Some(root) => self.validate_node(root, None, None, true)?.1,
+            None => 0,
+        };
+        if count != self.len {
+            return Err(InvariantViolation::LengthMismatch);
+        }
+        Ok(())
+    }
+
+    // Returns the leaf depth and key count of a valid subtree whose keys lie
+    // strictly between `lower` and `upper`.
+    fn validate_node(&self, node: &Node<T>, lower: Option<&T>, upper: Option<&T>, is_root: bool) -> Result<(usize, usize), InvariantViolation> {
+        check_node(&node.keys, &node.children, node.leaf, self.t, is_root, (lower, upper), &self.compare)?;
+        if node.leaf {
+            return Ok((0, node.keys.len()));
+        }
+        if node.children.iter().any(Option::is_none) {
+            return Err(InvariantViolation::ChildCount);
+        }
+
+        let mut depth = None;
+        let mut count = node.keys.len();
+        for (i, child) in node.children.iter().enumerate() {
+            let lower = if i == 0 { lower } else { Some(&node.keys[i - 1]) };
+            let upper = if i == node.keys.len() { upper } else { Some(&node.keys[i]) };
+            let (child_depth, child_count) = self.validate_node(child.as_ref().unwrap(), lower, upper, false)?;
+            if depth.is_some_and(|depth| depth != child_depth) {
+                return Err(InvariantViolation::LeafDepthMismatch);
+            }
+            depth = Some(child_depth);
+            count += child_count;
+        }
+        Ok((depth.unwrap() + 1, count))
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+}
+
+// Splits a node that has grown to 2t keys. The node keeps its first t - 1
+// keys and t children, key t - 1 becomes the separator, and the rest move to
+// the returned right half. The on-disk tree splits through here as well, so
+// both trees fill their nodes the same way.
+fn split_keys<T, L>(keys: &mut Vec<T>, children: &mut Vec<L>, t: usize) -> (T, Vec<T>, Vec<L>) {
+    let right_keys = keys.split_off(t);
+    let right_children = if children.is_empty() { Vec::new() } else { children.split_off(t) };
+    (keys.pop().unwrap(), right_keys, right_children)
+}
+
+// Checks a single node's key count, key order and child count, given the
+// keys that bound its subtree. Shared with the on-disk tree's `validate`.
+fn check_node<T, L, C: Compare<T>>(
+    keys: &[T],
+    children: &[L],
+    leaf: bool,
+    t: usize,
+    is_root: bool,
+    (lower, upper): (Option<&T>, Option<&T>),
+    compare: &C,
+) -> Result<(), InvariantViolation> {
+    let min_keys = if is_root { 1 } else { t - 1 };
+    if keys.len() < min_keys || keys.len() > 2 * t - 1 {
+        return Err(InvariantViolation::KeyCount);
+    }
+    if keys.windows(2).any(|pair| compare.compare(&pair[0], &pair[1]) != Ordering::Less)
+        || lower.is_some_and(|lower| compare.compare(&keys[0], lower) != Ordering::Greater)
+        || upper.is_some_and(|upper| compare.compare(&keys[keys.len() - 1], upper) != Ordering::Less)
+    {
+        return Err(InvariantViolation::OutOfOrder);
+    }
+    let expected = if leaf { 0 } else { keys.len() + 1 };
+    if children.len() != expected {
+        return Err(InvariantViolation::ChildCount);
+    }
+    Ok(())
+}
+
+pub struct Range<'a, T, R, C = Natural> {
+    stack: Vec<(&'a Node<T>, usize)>,
+    range: R,
+    compare: &'a C,
+}
+
+impl<'a, T, R: RangeBounds<T>, C: Compare<T>> Iterator for Range<'a, T, R, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, pos) = self.stack.last_mut()?;
+            let node: &'a Node<T> = node;
+            if *pos == node.keys.len() {
+                self.stack.pop();
+                continue;
+            }
+
+            let key = &node.keys[*pos];
+            *pos += 1;
+            if !bounds::below_end_by(self.compare, self.range.end_bound(), key) {
+                self.stack.clear();
+                return None;
+            }
+
+            if !node.leaf {
+                let mut current = node.children[*pos].as_deref();
+                while let Some(child) = current {
+                    self.stack.push((child, 0));
+                    current = if child.leaf { None } else { child.children[0].as_deref() };
+                }
+            }
+            return Some(key);
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    // Each entry holds a node and the next key position to visit from that end.
+    front: Vec<(&'a Node<T>, usize)>,
+    back: Vec<(&'a Node<T>, usize)>,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a Node<T>>) {
+        while let Some(current) = node {
+            self.front.push((current, 0));
+            node = if current.leaf { None } else { current.children[0].as_deref() };
+        }
+    }
+
+    fn push_right(&mut self, mut node: Option<&'a Node<T>>) {
+        while let Some(current) = node {
+            let pos = current.keys.len();
+            self.back.push((current, pos));
+            node = if current.leaf { None } else { current.children[pos].as_deref() };
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        loop {
+            let (node, pos) = self.front.last_mut()?;
+            let node: &'a Node<T> = node;
+            if *pos == node.keys.len() {
+                self.front.pop();
+                continue;
+            }
+
+            let key = &node.keys[*pos];
+            *pos += 1;
+            if !node.leaf {
+                let child = node.children[*pos].as_deref();
+                self.push_left(child);
+            }
+            self.len -= 1;
+            return Some(key);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        loop {
+            let (node, pos) = self.back.last_mut()?;
+            let node: &'a Node<T> = node;
+            if *pos == 0 {
+                self.back.pop();
+                continue;
+            }
+
+            *pos -= 1;
+            let key = &node.keys[*pos];
+            if !node.leaf {
+                let child = node.children[*pos].as_deref();
+                self.push_right(child);
+            }
+            self.len -= 1;
+            return Some(key);
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+pub struct IntoIter<T> {
+    inner: OwnedIter<Node<T>>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> Open for Node<T> {
+    type Item = T;
+
+    fn open(self: Box<Self>, parts: &mut Vec<Part<Self>>) {
+        let Node { keys, children, .. } = *self;
+        let mut children = children.into_iter().flatten();
+        for key in keys {
+            parts.extend(children.next().map(Part::Node));
+            parts.push(Part::Value(key));
+        }
+        parts.extend(children.next().map(Part::Node));
+    }
+}
+
+pub struct PreOrder<'a, T> {
+    stack: Vec<&'a Node<T>>,
+    keys: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for PreOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(key) = self.keys.next() {
+                return Some(key);
+            }
+            let node = self.stack.pop()?;
+            self.keys = node.keys.iter();
+            self.stack.extend(node.children.iter().rev().flatten().map(|child| &**child));
+        }
+    }
+}
+
+pub struct PostOrder<'a, T> {
+    // The flag records whether the node's children have already been pushed.
+    stack: Vec<(&'a Node<T>, bool)>,
+    keys: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for PostOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(key) = self.keys.next() {
+                return Some(key);
+            }
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                self.keys = node.keys.iter();
+                continue;
+            }
+            self.stack.push((node, true));
+            self.stack.extend(node.children.iter().rev().flatten().map(|child| (&**child, false)));
+        }
+    }
+}
+
+pub struct LevelOrder<'a, T> {
+    queue: VecDeque<&'a Node<T>>,
+    keys: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for LevelOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(key) = self.keys.next() {
+                return Some(key);
+            }
+            let node = self.queue.pop_front()?;
+            self.keys = node.keys.iter();
+            self.queue.extend(node.children.iter().flatten().map(|child| &**child));
+        }
+    }
+}
+
+impl<'a, T, C: Compare<T>> IntoIterator for &'a BTree<T, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T, C: Compare<T>> IntoIterator for BTree<T, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            inner: OwnedIter::new(self.root, self.len),
+        }
+    }
+}
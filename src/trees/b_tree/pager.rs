@@ -0,0 +1,218 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+pub(crate) const PAGE_SIZE: usize = 4096;
+
+pub(crate) type Page = Box<[u8; PAGE_SIZE]>;
+
+// A log frame is a page id, a commit flag, a checksum and the page image.
+const FRAME_HEADER: usize = 24;
+const FRAME_SIZE: usize = FRAME_HEADER + PAGE_SIZE;
+const CHECKPOINT_FRAMES: u64 = 1024;
+
+struct CachedPage {
+    data: Page,
+    dirty: bool,
+    last_used: u64,
+}
+
+// Fixed-size page I/O over a single file with a small cache and a redo log.
+//
+// Modified pages stay in the cache until `commit` appends them to the log;
+// the main file is only written by `checkpoint`, so it always holds the
+// state as of the last checkpoint and the log holds every commit since.
+//
+// The cache sits behind a `RefCell` so that reads can go through `&self`:
+// loading a page or bumping its recency changes no visible state.
+pub(crate) struct Pager {
+    file: File,
+    wal: File,
+    // Offset in the log of the latest committed image of each page.
+    wal_index: HashMap<u64, u64>,
+    wal_len: u64,
+    cache: RefCell<HashMap<u64, CachedPage>>,
+    capacity: usize,
+    clock: Cell<u64>,
+}
+
+impl Pager {
+    // Opens the pager, replaying every fully committed transaction found in
+    // the log. Frames after the last intact commit are discarded.
+    pub(crate) fn open(file: File, wal: File, capacity: usize) -> io::Result<Self> {
+        let mut pager = Pager {
+            file,
+            wal,
+            wal_index: HashMap::new(),
+            wal_len: 0,
+            cache: RefCell::new(HashMap::new()),
+            capacity,
+            clock: Cell::new(0),
+        };
+        pager.replay()?;
+        Ok(pager)
+    }
+
+    fn replay(&mut self) -> io::Result<()> {
+        let mut log = Vec::new();
+        self.wal.seek(SeekFrom::Start(0))?;
+        self.wal.read_to_end(&mut log)?;
+
+        let mut pending = Vec::new();
+        let mut offset = 0;
+        while offset + FRAME_SIZE <= log.len() {
+            let frame = &log[offset..offset + FRAME_SIZE];
+            let id = u64::from_le_bytes(frame[0..8].try_into().unwrap());
+            let commit = u64::from_le_bytes(frame[8..16].try_into().unwrap());
+            let sum = u64::from_le_bytes(frame[16..24].try_into().unwrap());
+            if sum != checksum(id, commit, &frame[FRAME_HEADER..]) {
+                break;
+            }
+            pending.push((id, (offset + FRAME_HEADER) as u64));
+            offset += FRAME_SIZE;
+            if commit == 1 {
+                self.wal_index.extend(pending.drain(..));
+                self.wal_len = offset as u64;
+            }
+        }
+
+        self.wal.set_len(self.wal_len)?;
+        self.wal.sync_all()
+    }
+
+    pub(crate) fn file_len(&self) -> io::Result<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+
+    // Calls `f` with the contents of page `id`, loading it into the cache
+    // first if needed. `f` must not read from the pager itself.
+    pub(crate) fn read<R>(&self, id: u64, f: impl FnOnce(&[u8]) -> R) -> io::Result<R> {
+        self.clock.set(self.clock.get() + 1);
+        if !self.cache.borrow().contains_key(&id) {
+            let mut data: Page = Box::new([0; PAGE_SIZE]);
+            match self.wal_index.get(&id) {
+                Some(&offset) => read_page(&self.wal, offset, &mut data)?,
+                None => read_page(&self.file, id * PAGE_SIZE as u64, &mut data)?,
+            }
+            self.insert(id, data, false);
+        }
+        let mut cache = self.cache.borrow_mut();
+        let page = cache.get_mut(&id).unwrap();
+        page.last_used = self.clock.get();
+        Ok(f(&page.data[..]))
+    }
+
+    pub(crate) fn write(&mut self, id: u64, data: Page) {
+        self.clock.set(self.clock.get() + 1);
+        self.insert(id, data, true);
+    }
+
+    fn insert(&self, id: u64, data: Page, dirty: bool) {
+        let mut cache = self.cache.borrow_mut();
+        if !cache.contains_key(&id) && cache.len() >= self.capacity {
+            evict(&mut cache);
+        }
+        let dirty = dirty || cache.get(&id).is_some_and(|page| page.dirty);
+        cache.insert(id, CachedPage {
+            data,
+            dirty,
+            last_used: self.clock.get(),
+        });
+    }
+
+    // Appends every dirty page to the log as one transaction and makes it
+    // durable. The last frame carries the commit flag.
+    pub(crate) fn commit(&mut self) -> io::Result<()> {
+        let cache = self.cache.get_mut();
+        let mut dirty: Vec<u64> = cache.iter().filter(|(_, page)| page.dirty).map(|(&id, _)| id).collect();
+        if dirty.is_empty() {
+            return Ok(());
+        }
+        dirty.sort_unstable();
+
+        let mut frames = Vec::with_capacity(dirty.len() * FRAME_SIZE);
+        for (i, &id) in dirty.iter().enumerate() {
+            let commit = (i + 1 == dirty.len()) as u64;
+            let data = &cache[&id].data[..];
+            frames.extend_from_slice(&id.to_le_bytes());
+            frames.extend_from_slice(&commit.to_le_bytes());
+            frames.extend_from_slice(&checksum(id, commit, data).to_le_bytes());
+            frames.extend_from_slice(data);
+        }
+        self.wal.seek(SeekFrom::Start(self.wal_len))?;
+        self.wal.write_all(&frames)?;
+        self.wal.sync_data()?;
+
+        for (i, id) in dirty.into_iter().enumerate() {
+            self.wal_index.insert(id, self.wal_len + (i * FRAME_SIZE + FRAME_HEADER) as u64);
+            cache.get_mut(&id).unwrap().dirty = false;
+        }
+        self.wal_len += frames.len() as u64;
+
+        if self.wal_len >= CHECKPOINT_FRAMES * FRAME_SIZE as u64 {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    // Discards every change made since the last commit.
+    pub(crate) fn rollback(&mut self) {
+        self.cache.get_mut().retain(|_, page| !page.dirty);
+    }
+
+    // Copies the latest committed image of every logged page into the main
+    // file, then empties the log. A crash part-way through leaves the log
+    // intact, and replaying it again on open is harmless.
+    pub(crate) fn checkpoint(&mut self) -> io::Result<()> {
+        if self.wal_index.is_empty() {
+            return Ok(());
+        }
+        let mut pages: Vec<(u64, u64)> = self.wal_index.iter().map(|(&id, &offset)| (id, offset)).collect();
+        pages.sort_unstable();
+
+        let mut data: Page = Box::new([0; PAGE_SIZE]);
+        for (id, offset) in pages {
+            read_page(&self.wal, offset, &mut data)?;
+            self.file.seek(SeekFrom::Start(id * PAGE_SIZE as u64))?;
+            self.file.write_all(&data[..])?;
+        }
+        self.file.sync_all()?;
+
+        self.wal.set_len(0)?;
+        self.wal.sync_all()?;
+        self.wal_index.clear();
+        self.wal_len = 0;
+        Ok(())
+    }
+}
+
+// Drops the least recently used clean page. Dirty pages are pinned until
+// they are committed, so the cache may briefly grow past its capacity.
+fn evict(cache: &mut HashMap<u64, CachedPage>) {
+    let victim = cache
+        .iter()
+        .filter(|(_, page)| !page.dirty)
+        .min_by_key(|(_, page)| page.last_used)
+        .map(|(&id, _)| id);
+    if let Some(id) = victim {
+        cache.remove(&id);
+    }
+}
+
+fn read_page(mut file: &File, offset: u64, data: &mut [u8; PAGE_SIZE]) -> io::Result<()> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut data[..])
+}
+
+// FNV-1a style mixing over the frame header fields and the page image as
+// 64-bit words, enough to reject a torn or partially written frame.
+fn checksum(id: u64, commit: u64, data: &[u8]) -> u64 {
+    let words = data.chunks_exact(8).map(|word| u64::from_le_bytes(word.try_into().unwrap()));
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for word in [id, commit].into_iter().chain(words) {
+        hash ^= word;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
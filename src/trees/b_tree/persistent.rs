@@ -0,0 +1,513 @@
+use std::fs::OpenOptions;
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use super::pager::{Page, Pager, PAGE_SIZE};
+use super::{check_node, split_keys};
+use crate::trees::invariant::InvariantViolation;
+use crate::utils::compare::Natural;
+
+const MAGIC: &[u8; 8] = b"BTREEPG1";
+const CACHE_PAGES: usize = 64;
+
+const LEAF: u8 = 1;
+const INTERNAL: u8 = 2;
+const FREE: u8 = 3;
+
+// Keys are stored inline in pages, so they need a fixed-width encoding.
+pub trait PageKey: Ord + Clone {
+    const SIZE: usize;
+
+    fn write_to(&self, buf: &mut [u8]);
+    fn read_from(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_page_key {
+    ($($t:ty),*) => {
+        $(
+            impl PageKey for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn write_to(&self, buf: &mut [u8]) {
+                    buf[..Self::SIZE].copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_from(buf: &[u8]) -> Self {
+                    <$t>::from_le_bytes(buf[..Self::SIZE].try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_page_key!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+// Page 0 holds the header; node pages are numbered from 1, so 0 doubles as
+// the null page id.
+struct Header {
+    t: usize,
+    root: u64,
+    len: usize,
+    page_count: u64,
+    free_head: u64,
+}
+
+struct Node<K> {
+    keys: Vec<K>,
+    children: Vec<u64>,
+    leaf: bool,
+}
+
+pub struct PersistentBTree<K> {
+    pager: Pager,
+    header: Header,
+    _marker: PhantomData<K>,
+}
+
+impl<K: PageKey> PersistentBTree<K> {
+    pub fn open<P: AsRef<Path>>(path: P, t: usize) -> io::Result<Self> {
+        if t < 2 || Self::node_size(t) > PAGE_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "minimum degree does not fit in a page"));
+        }
+        let mut wal_path = path.as_ref().as_os_str().to_owned();
+        wal_path.push("-wal");
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let wal = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(wal_path)?;
+        let mut tree = PersistentBTree {
+            pager: Pager::open(file, wal, CACHE_PAGES)?,
+            header: Header {
+                t,
+                root: 0,
+                len: 0,
+                page_count: 1,
+                free_head: 0,
+            },
+            _marker: PhantomData,
+        };
+
+        // Fold whatever the log recovered into the main file before use.
+        tree.pager.checkpoint()?;
+        if tree.pager.file_len()? == 0 {
+            tree.write_header();
+            tree.pager.commit()?;
+            tree.pager.checkpoint()?;
+        } else {
+            tree.read_header()?;
+        }
+        Ok(tree)
+    }
+
+    // Every `insert` and `remove` is durable in the log once it returns;
+    // checkpointing copies the logged pages into the main file and empties
+    // the log.
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        self.pager.checkpoint()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.checkpoint()
+    }
+
+    pub fn close(mut self) -> io::Result<()> {
+        self.checkpoint()
+    }
+
+    // Runs `op` as a single logged transaction. On failure every page it
+    // touched is discarded, leaving the tree as of the last commit.
+    fn transaction<R, F: FnOnce(&mut Self) -> io::Result<R>>(&mut self, op: F) -> io::Result<R> {
+        let result = op(self).and_then(|result| {
+            self.write_header();
+            self.pager.commit()?;
+            Ok(result)
+        });
+        if result.is_err() {
+            self.pager.rollback();
+            self.read_header()?;
+        }
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.header.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.header.len == 0
+    }
+
+    fn node_size(t: usize) -> usize {
+        3 + (2 * t - 1) * K::SIZE + 2 * t * 8
+    }
+
+    fn read_header(&mut self) -> io::Result<()> {
+        let header = &mut self.header;
+        self.pager.read(0, |page| {
+            if &page[0..8] != MAGIC {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "not a B-tree page file"));
+            }
+            let t = read_u64(page, 8) as usize;
+            let key_size = read_u64(page, 16) as usize;
+            if t != header.t || key_size != K::SIZE {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "file was created with a different degree or key type"));
+            }
+            header.root = read_u64(page, 24);
+            header.len = read_u64(page, 32) as usize;
+            header.page_count = read_u64(page, 40);
+            header.free_head = read_u64(page, 48);
+            Ok(())
+        })?
+    }
+
+    fn write_header(&mut self) {
+        let mut page: Page = Box::new([0; PAGE_SIZE]);
+        page[0..8].copy_from_slice(MAGIC);
+        write_u64(&mut page[..], 8, self.header.t as u64);
+        write_u64(&mut page[..], 16, K::SIZE as u64);
+        write_u64(&mut page[..], 24, self.header.root);
+        write_u64(&mut page[..], 32, self.header.len as u64);
+        write_u64(&mut page[..], 40, self.header.page_count);
+        write_u64(&mut page[..], 48, self.header.free_head);
+        self.pager.write(0, page);
+    }
+
+    fn read_node(&self, id: u64) -> io::Result<Node<K>> {
+        let t = self.header.t;
+        self.pager.read(id, |page| {
+            let leaf = match page[0] {
+                LEAF => true,
+                INTERNAL => false,
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "page is not a B-tree node")),
+            };
+            let count = u16::from_le_bytes([page[1], page[2]]) as usize;
+            let keys = (0..count).map(|i| K::read_from(&page[3 + i * K::SIZE..])).collect();
+            let offset = 3 + (2 * t - 1) * K::SIZE;
+            let children = if leaf {
+                vec![]
+            } else {
+                (0..=count).map(|i| read_u64(page, offset + i * 8)).collect()
+            };
+            Ok(Node { keys, children, leaf })
+        })?
+    }
+
+    fn write_node(&mut self, id: u64, node: &Node<K>) {
+        let t = self.header.t;
+        let mut page: Page = Box::new([0; PAGE_SIZE]);
+        page[0] = if node.leaf { LEAF } else { INTERNAL };
+        page[1..3].copy_from_slice(&(node.keys.len() as u16).to_le_bytes());
+        for (i, key) in node.keys.iter().enumerate() {
+            key.write_to(&mut page[3 + i * K::SIZE..]);
+        }
+        let offset = 3 + (2 * t - 1) * K::SIZE;
+        for (i, &child) in node.children.iter().enumerate() {
+            write_u64(&mut page[..], offset + i * 8, child);
+        }
+        self.pager.write(id, page);
+    }
+
+    fn allocate(&mut self, node: &Node<K>) -> io::Result<u64> {
+        let id = if self.header.free_head != 0 {
+            let id = self.header.free_head;
+            self.header.free_head = self.pager.read(id, |page| read_u64(page, 1))?;
+            id
+        } else {
+            self.header.page_count += 1;
+            self.header.page_count - 1
+        };
+        self.write_node(id, node);
+        Ok(id)
+    }
+
+    fn release(&mut self, id: u64) {
+        let mut page: Page = Box::new([0; PAGE_SIZE]);
+        page[0] = FREE;
+        write_u64(&mut page[..], 1, self.header.free_head);
+        self.header.free_head = id;
+        self.pager.write(id, page);
+    }
+
+    pub fn contains(&self, key: &K) -> io::Result<bool> {
+        let mut current = self.header.root;
+        while current != 0 {
+            let node = self.read_node(current)?;
+            match node.keys.binary_search(key) {
+                Ok(_) => return Ok(true),
+                Err(_) if node.leaf => return Ok(false),
+                Err(pos) => current = node.children[pos],
+            }
+        }
+        Ok(false)
+    }
+
+    // Checks the same invariants as `BTree::validate`, reporting a violation
+    // as an `InvalidData` error.
+    pub fn validate(&self) -> io::Result<()> {
+        let count = match self.header.root {
+            0 => 0,
+            root => self.validate_node(root, None, None, true)?.1,
+        };
+        if count != self.header.len {
+            return Err(corrupt(InvariantViolation::LengthMismatch));
+        }
+        Ok(())
+    }
+
+    // Returns the leaf depth and key count of a valid subtree whose keys lie
+    // strictly between `lower` and `upper`.
+    fn validate_node(&self, id: u64, lower: Option<&K>, upper: Option<&K>, is_root: bool) -> io::Result<(usize, usize)> {
+        let node = self.read_node(id)?;
+        check_node(&node.keys, &node.children, node.leaf, self.header.t, is_root, (lower, upper), &Natural).map_err(corrupt)?;
+        if node.leaf {
+            return Ok((0, node.keys.len()));
+        }
+
+        let mut depth = None;
+        let mut count = node.keys.len();
+        for (i, &child) in node.children.iter().enumerate() {
+            let lower = if i == 0 { lower } else { Some(&node.keys[i - 1]) };
+            let upper = if i == node.keys.len() { upper } else { Some(&node.keys[i]) };
+            let (child_depth, child_count) = self.validate_node(child, lower, upper, false)?;
+            if depth.is_some_and(|depth| depth != child_depth) {
+                return Err(corrupt(InvariantViolation::LeafDepthMismatch));
+            }
+            depth = Some(child_depth);
+            count += child_count;
+        }
+        Ok((depth.unwrap() + 1, count))
+    }
+
+    pub fn insert(&mut self, key: K) -> io::Result<()> {
+        self.transaction(|tree| tree.insert_key(key))
+    }
+
+    fn insert_key(&mut self, key: K) -> io::Result<()> {
+        if self.header.root == 0 {
+            self.header.root = self.allocate(&Node {
+                keys: vec![key],
+                children: vec![],
+                leaf: true,
+            })?;
+            self.header.len += 1;
+            return Ok(());
+        }
+
+        let (inserted, split) = self.insert_into(self.header.root, key)?;
+        if let Some((median, right)) = split {
+            self.header.root = self.allocate(&Node {
+                keys: vec![median],
+                children: vec![self.header.root, right],
+                leaf: false,
+            })?;
+        }
+        if inserted {
+            self.header.len += 1;
+        }
+        Ok(())
+    }
+
+    // Inserts `key` below page `id` the same way `BTree::insert_into` does:
+    // into a leaf first, then splitting each node that overflowed on the way
+    // back up. Returns whether the key was new, and the separator and right
+    // sibling's page if `id` was split. A node only holds 2t keys in memory;
+    // it is split before it is written, so it always fits its page.
+    fn insert_into(&mut self, id: u64, key: K) -> io::Result<(bool, Option<(K, u64)>)> {
+        let mut node = self.read_node(id)?;
+        let pos = match node.keys.binary_search(&key) {
+            Ok(_) => return Ok((false, None)),
+            Err(pos) => pos,
+        };
+        if node.leaf {
+            node.keys.insert(pos, key);
+        } else {
+            let (inserted, split) = self.insert_into(node.children[pos], key)?;
+            let Some((median, right)) = split else {
+                return Ok((inserted, None));
+            };
+            node.keys.insert(pos, median);
+            node.children.insert(pos + 1, right);
+        }
+
+        let t = self.header.t;
+        let split = if node.keys.len() == 2 * t {
+            let (median, keys, children) = split_keys(&mut node.keys, &mut node.children, t);
+            let right = self.allocate(&Node {
+                keys,
+                children,
+                leaf: node.leaf,
+            })?;
+            Some((median, right))
+        } else {
+            None
+        };
+        self.write_node(id, &node);
+        Ok((true, split))
+    }
+
+    pub fn remove(&mut self, key: &K) -> io::Result<bool> {
+        self.transaction(|tree| tree.remove_key(key))
+    }
+
+    fn remove_key(&mut self, key: &K) -> io::Result<bool> {
+        if self.header.root == 0 {
+            return Ok(false);
+        }
+
+        let removed = self.remove_from(self.header.root, key)?;
+
+        // Shrink the tree when the root has been emptied by a merge.
+        let root = self.read_node(self.header.root)?;
+        if root.keys.is_empty() {
+            self.release(self.header.root);
+            self.header.root = if root.leaf { 0 } else { root.children[0] };
+        }
+        if removed {
+            self.header.len -= 1;
+        }
+        Ok(removed)
+    }
+
+    fn remove_from(&mut self, id: u64, key: &K) -> io::Result<bool> {
+        let t = self.header.t;
+        let mut node = self.read_node(id)?;
+        match node.keys.binary_search(key) {
+            Ok(pos) if node.leaf => {
+                node.keys.remove(pos);
+                self.write_node(id, &node);
+                Ok(true)
+            }
+            Ok(pos) => {
+                if self.read_node(node.children[pos])?.keys.len() >= t {
+                    node.keys[pos] = self.remove_max(node.children[pos])?;
+                    self.write_node(id, &node);
+                } else if self.read_node(node.children[pos + 1])?.keys.len() >= t {
+                    node.keys[pos] = self.remove_min(node.children[pos + 1])?;
+                    self.write_node(id, &node);
+                } else {
+                    self.merge_children(&mut node, pos)?;
+                    self.write_node(id, &node);
+                    self.remove_from(node.children[pos], key)?;
+                }
+                Ok(true)
+            }
+            Err(_) if node.leaf => Ok(false),
+            Err(pos) => {
+                let pos = self.fill_child(&mut node, pos)?;
+                self.write_node(id, &node);
+                self.remove_from(node.children[pos], key)
+            }
+        }
+    }
+
+    fn remove_max(&mut self, id: u64) -> io::Result<K> {
+        let mut node = self.read_node(id)?;
+        if node.leaf {
+            let key = node.keys.pop().unwrap();
+            self.write_node(id, &node);
+            return Ok(key);
+        }
+        let last = node.keys.len();
+        let pos = self.fill_child(&mut node, last)?;
+        self.write_node(id, &node);
+        self.remove_max(node.children[pos])
+    }
+
+    fn remove_min(&mut self, id: u64) -> io::Result<K> {
+        let mut node = self.read_node(id)?;
+        if node.leaf {
+            let key = node.keys.remove(0);
+            self.write_node(id, &node);
+            return Ok(key);
+        }
+        let pos = self.fill_child(&mut node, 0)?;
+        self.write_node(id, &node);
+        self.remove_min(node.children[pos])
+    }
+
+    // Ensures the child at `pos` holds at least `t` keys before descending
+    // into it. Returns the index of the child that now covers the original
+    // key range. The caller is responsible for writing `node`.
+    fn fill_child(&mut self, node: &mut Node<K>, pos: usize) -> io::Result<usize> {
+        let t = self.header.t;
+        if self.read_node(node.children[pos])?.keys.len() >= t {
+            return Ok(pos);
+        }
+
+        if pos > 0 && self.read_node(node.children[pos - 1])?.keys.len() >= t {
+            self.borrow_from_prev(node, pos)?;
+            Ok(pos)
+        } else if pos < node.keys.len() && self.read_node(node.children[pos + 1])?.keys.len() >= t {
+            self.borrow_from_next(node, pos)?;
+            Ok(pos)
+        } else if pos < node.keys.len() {
+            self.merge_children(node, pos)?;
+            Ok(pos)
+        } else {
+            self.merge_children(node, pos - 1)?;
+            Ok(pos - 1)
+        }
+    }
+
+    fn borrow_from_prev(&mut self, node: &mut Node<K>, pos: usize) -> io::Result<()> {
+        let mut sibling = self.read_node(node.children[pos - 1])?;
+        let mut child = self.read_node(node.children[pos])?;
+
+        let key = std::mem::replace(&mut node.keys[pos - 1], sibling.keys.pop().unwrap());
+        child.keys.insert(0, key);
+        if !child.leaf {
+            child.children.insert(0, sibling.children.pop().unwrap());
+        }
+
+        self.write_node(node.children[pos - 1], &sibling);
+        self.write_node(node.children[pos], &child);
+        Ok(())
+    }
+
+    fn borrow_from_next(&mut self, node: &mut Node<K>, pos: usize) -> io::Result<()> {
+        let mut sibling = self.read_node(node.children[pos + 1])?;
+        let mut child = self.read_node(node.children[pos])?;
+
+        let key = std::mem::replace(&mut node.keys[pos], sibling.keys.remove(0));
+        child.keys.push(key);
+        if !child.leaf {
+            child.children.push(sibling.children.remove(0));
+        }
+
+        self.write_node(node.children[pos + 1], &sibling);
+        self.write_node(node.children[pos], &child);
+        Ok(())
+    }
+
+    fn merge_children(&mut self, node: &mut Node<K>, pos: usize) -> io::Result<()> {
+        let sibling_id = node.children.remove(pos + 1);
+        let sibling = self.read_node(sibling_id)?;
+        let mut child = self.read_node(node.children[pos])?;
+
+        child.keys.push(node.keys.remove(pos));
+        child.keys.extend(sibling.keys);
+        child.children.extend(sibling.children);
+
+        self.write_node(node.children[pos], &child);
+        self.release(sibling_id);
+        Ok(())
+    }
+}
+
+impl<K> Drop for PersistentBTree<K> {
+    fn drop(&mut self) {
+        // Errors cannot be reported from drop; call `close` to observe them.
+        let _ = self.pager.checkpoint();
+    }
+}
+
+fn corrupt(violation: InvariantViolation) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, violation)
+}
+
+fn read_u64(page: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(page[offset..offset + 8].try_into().unwrap())
+}
+
+fn write_u64(page: &mut [u8], offset: usize, value: u64) {
+    page[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
@@ -0,0 +1,59 @@
+use super::b_tree::{BTree, Iter};
+use super::map_tree::{KeyOrder, MapTree, Position};
+use super::ordered_map::OrderedMap;
+
+pub type BTreeOrderedMap<K, V> = OrderedMap<BTree<(K, V), KeyOrder>>;
+
+impl<K: Ord, V> BTreeOrderedMap<K, V> {
+    pub fn new(t: usize) -> Self {
+        OrderedMap::from_tree(BTree::with_comparator(t, KeyOrder))
+    }
+}
+
+impl<K: Ord, V> MapTree for BTree<(K, V), KeyOrder> {
+    type Key = K;
+    type Value = V;
+    type Iter<'a>
+        = Iter<'a, (K, V)>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn search(&self, key: &K) -> Result<Position, Position> {
+        self.search_by(|(other, _)| key.cmp(other)).map(Position::Path).map_err(Position::Path)
+    }
+
+    fn value_at(&mut self, position: &Position) -> &mut V {
+        let Position::Path(path) = position else {
+            unreachable!("B-tree maps only hand out paths")
+        };
+        &mut self.get_at_mut(path).1
+    }
+
+    fn insert_at(&mut self, position: Position, key: K, value: V) -> &mut V {
+        let Position::Path(path) = position else {
+            unreachable!("B-tree maps only hand out paths")
+        };
+        let path = self.insert_at(&path, (key, value));
+        &mut self.get_at_mut(&path).1
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove_by(|(other, _)| key.cmp(other)).map(|(_, value)| value)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get_by(|(other, _)| key.cmp(other)).map(|(_, value)| value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_by_mut(|(other, _)| key.cmp(other)).map(|(_, value)| value)
+    }
+
+    fn iter(&self) -> Iter<'_, (K, V)> {
+        self.iter()
+    }
+}
@@ -0,0 +1,233 @@
+use std::cmp::Ordering;
+use std::ops::RangeBounds;
+
+use super::bounds;
+use super::summary::Summary;
+use crate::utils::compare::Compare;
+
+pub(crate) type Link<N> = Option<Box<N>>;
+
+// The node layout shared by `AVLTree` and `RedBlackTree`, so the searches,
+// range walks and set operations below are written once for both.
+pub(crate) trait BinaryNode: Sized {
+    type Value;
+    type Summary;
+
+    fn value(&self) -> &Self::Value;
+    fn summary(&self) -> &Self::Summary;
+    fn left(&self) -> Option<&Self>;
+    fn right(&self) -> Option<&Self>;
+    fn take_children(&mut self) -> (Link<Self>, Link<Self>);
+}
+
+// Join-based set operations. A tree supplies `join` and `join_pair`, which
+// are the only places its balancing rules come in, and gets splitting,
+// union, intersection and both differences from them.
+pub(crate) trait Join {
+    type Node: BinaryNode;
+
+    // Joins two trees around a detached middle node, where every value in
+    // `left` is less than `mid`'s value and every value in `right` is greater.
+    fn join(left: Link<Self::Node>, mid: Box<Self::Node>, right: Link<Self::Node>) -> Box<Self::Node>;
+
+    // Joins two trees where every value in `left` is less than every value in `right`.
+    fn join_pair(left: Link<Self::Node>, right: Link<Self::Node>) -> Link<Self::Node>;
+
+    // Splits the subtree into the values less than and greater than `value`,
+    // returning the detached node holding `value` if there is one.
+    fn split<C: Compare<Value<Self>>>(compare: &C, node: Link<Self::Node>, value: &Value<Self>) -> Split<Self::Node> {
+        let mut node = match node {
+            Some(node) => node,
+            None => return (None, None, None),
+        };
+
+        let (left, right) = node.take_children();
+        match compare.compare(value, node.value()) {
+            Ordering::Equal => (left, Some(node), right),
+            Ordering::Less => {
+                let (less, found, greater) = Self::split(compare, left, value);
+                (less, found, Some(Self::join(greater, node, right)))
+            }
+            Ordering::Greater => {
+                let (less, found, greater) = Self::split(compare, right, value);
+                (Some(Self::join(left, node, less)), found, greater)
+            }
+        }
+    }
+
+    fn union_nodes<C: Compare<Value<Self>>>(compare: &C, a: Link<Self::Node>, b: Link<Self::Node>) -> Link<Self::Node> {
+        let mut a = match a {
+            Some(a) => a,
+            None => return b,
+        };
+        if b.is_none() {
+            return Some(a);
+        }
+
+        let (left, right) = a.take_children();
+        let (b_left, _, b_right) = Self::split(compare, b, a.value());
+        let left = Self::union_nodes(compare, left, b_left);
+        let right = Self::union_nodes(compare, right, b_right);
+        Some(Self::join(left, a, right))
+    }
+
+    fn intersection_nodes<C: Compare<Value<Self>>>(compare: &C, a: Link<Self::Node>, b: Link<Self::Node>) -> Link<Self::Node> {
+        let (mut a, b) = match (a, b) {
+            (Some(a), b @ Some(_)) => (a, b),
+            _ => return None,
+        };
+
+        let (left, right) = a.take_children();
+        let (b_left, found, b_right) = Self::split(compare, b, a.value());
+        let left = Self::intersection_nodes(compare, left, b_left);
+        let right = Self::intersection_nodes(compare, right, b_right);
+        match found {
+            Some(_) => Some(Self::join(left, a, right)),
+            None => Self::join_pair(left, right),
+        }
+    }
+
+    fn difference_nodes<C: Compare<Value<Self>>>(compare: &C, a: Link<Self::Node>, b: Link<Self::Node>) -> Link<Self::Node> {
+        let (a, mut b) = match (a, b) {
+            (None, _) => return None,
+            (a, None) => return a,
+            (a, Some(b)) => (a, b),
+        };
+
+        let (a_left, _, a_right) = Self::split(compare, a, b.value());
+        let (b_left, b_right) = b.take_children();
+        let left = Self::difference_nodes(compare, a_left, b_left);
+        let right = Self::difference_nodes(compare, a_right, b_right);
+        Self::join_pair(left, right)
+    }
+
+    fn symmetric_difference_nodes<C: Compare<Value<Self>>>(compare: &C, a: Link<Self::Node>, b: Link<Self::Node>) -> Link<Self::Node> {
+        let mut a = match a {
+            Some(a) => a,
+            None => return b,
+        };
+        if b.is_none() {
+            return Some(a);
+        }
+
+        let (left, right) = a.take_children();
+        let (b_left, found, b_right) = Self::split(compare, b, a.value());
+        let left = Self::symmetric_difference_nodes(compare, left, b_left);
+        let right = Self::symmetric_difference_nodes(compare, right, b_right);
+        match found {
+            Some(_) => Self::join_pair(left, right),
+            None => Some(Self::join(left, a, right)),
+        }
+    }
+}
+
+type Value<J> = <<J as Join>::Node as BinaryNode>::Value;
+
+// The values less than, equal to and greater than a split point.
+type Split<N> = (Link<N>, Link<N>, Link<N>);
+
+// The greatest value that is at most `value`, or, with `strict`, less than it.
+pub(crate) fn floor<'a, N: BinaryNode, C: Compare<N::Value>>(compare: &C, root: Option<&'a N>, value: &N::Value, strict: bool) -> Option<&'a N::Value> {
+    let mut current = root;
+    let mut best = None;
+    while let Some(node) = current {
+        match compare.compare(node.value(), value) {
+            Ordering::Equal if !strict => return Some(node.value()),
+            Ordering::Less => {
+                best = Some(node.value());
+                current = node.right();
+            }
+            _ => current = node.left(),
+        }
+    }
+    best
+}
+
+// The least value that is at least `value`, or, with `strict`, greater than it.
+pub(crate) fn ceiling<'a, N: BinaryNode, C: Compare<N::Value>>(compare: &C, root: Option<&'a N>, value: &N::Value, strict: bool) -> Option<&'a N::Value> {
+    let mut current = root;
+    let mut best = None;
+    while let Some(node) = current {
+        match compare.compare(node.value(), value) {
+            Ordering::Equal if !strict => return Some(node.value()),
+            Ordering::Greater => {
+                best = Some(node.value());
+                current = node.left();
+            }
+            _ => current = node.right(),
+        }
+    }
+    best
+}
+
+// `above` and `below` record whether every value in the subtree is already
+// known to satisfy the start and end bounds of `range`. A subtree that
+// satisfies both contributes its stored summary whole.
+pub(crate) fn fold<N, R, C>(compare: &C, node: Option<&N>, range: &R, above: bool, below: bool) -> N::Summary
+where
+    N: BinaryNode,
+    N::Summary: Summary<N::Value>,
+    R: RangeBounds<N::Value>,
+    C: Compare<N::Value>,
+{
+    let node = match node {
+        Some(node) => node,
+        None => return N::Summary::empty(),
+    };
+
+    if above && below {
+        return node.summary().clone();
+    }
+    if !above && !bounds::above_start_by(compare, range.start_bound(), node.value()) {
+        return fold(compare, node.right(), range, above, below);
+    }
+    if !below && !bounds::below_end_by(compare, range.end_bound(), node.value()) {
+        return fold(compare, node.left(), range, above, below);
+    }
+    let left = fold(compare, node.left(), range, above, true);
+    let right = fold(compare, node.right(), range, true, below);
+    left.combine(&N::Summary::of(node.value())).combine(&right)
+}
+
+// An in-order walk over the values in a range. The stack holds the nodes
+// whose value and right subtree are still to be visited.
+pub(crate) struct Range<'a, N, R, C> {
+    stack: Vec<&'a N>,
+    range: R,
+    compare: &'a C,
+}
+
+impl<'a, N: BinaryNode, R: RangeBounds<N::Value>, C: Compare<N::Value>> Range<'a, N, R, C> {
+    pub(crate) fn new(root: Option<&'a N>, range: R, compare: &'a C) -> Self {
+        let mut stack = Vec::new();
+        let mut current = root;
+        while let Some(node) = current {
+            if bounds::above_start_by(compare, range.start_bound(), node.value()) {
+                stack.push(node);
+                current = node.left();
+            } else {
+                current = node.right();
+            }
+        }
+        Range { stack, range, compare }
+    }
+}
+
+impl<'a, N: BinaryNode, R: RangeBounds<N::Value>, C: Compare<N::Value>> Iterator for Range<'a, N, R, C> {
+    type Item = &'a N::Value;
+
+    fn next(&mut self) -> Option<&'a N::Value> {
+        let node = self.stack.pop()?;
+        if !bounds::below_end_by(self.compare, self.range.end_bound(), node.value()) {
+            self.stack.clear();
+            return None;
+        }
+
+        let mut current = node.right();
+        while let Some(child) = current {
+            self.stack.push(child);
+            current = child.left();
+        }
+        Some(node.value())
+    }
+}
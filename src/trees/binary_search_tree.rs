@@ -1,70 +1,324 @@
 use std::cmp::Ordering;
+use std::collections::VecDeque;
 
-#[derive(Debug, Clone)]
+use super::invariant::InvariantViolation;
+use super::owned_iter::{Open, OwnedIter, Part};
+
+type Link<T> = Option<Box<TreeNode<T>>>;
+
+#[derive(Debug)]
 pub struct BinarySearchTree<T> {
-    root: Option<Box<TreeNode<T>>>,
+    root: Link<T>,
+    len: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 struct TreeNode<T> {
     value: T,
-    left: Option<Box<TreeNode<T>>>,
-    right: Option<Box<TreeNode<T>>>,
+    left: Link<T>,
+    right: Link<T>,
 }
 
 impl<T: Ord> BinarySearchTree<T> {
     pub fn new() -> Self {
-        BinarySearchTree { root: None }
+        BinarySearchTree { root: None, len: 0 }
     }
 
     pub fn insert(&mut self, value: T) {
-        self.root = Self::insert_node(self.root.take(), value);
-    }
-
-    fn insert_node(node: Option<Box<TreeNode<T>>>, value: T) -> Option<Box<TreeNode<T>>> {
-        match node {
-            Some(mut node) => {
-                match value.cmp(&node.value) {
-                    Ordering::Less => node.left = Self::insert_node(node.left.take(), value),
-                    Ordering::Greater => node.right = Self::insert_node(node.right.take(), value),
-                    Ordering::Equal => {} // Value already exists, do nothing
-                }
-                Some(node)
+        // Walk down iteratively: nothing keeps an unbalanced tree shallow.
+        let mut current = &mut self.root;
+        while let Some(node) = current {
+            current = match value.cmp(&node.value) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return, // Value already exists, do nothing
+            };
+        }
+        *current = Some(Box::new(TreeNode {
+            value,
+            left: None,
+            right: None,
+        }));
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            current = match value.cmp(&node.value) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter.push_right(self.root.as_deref());
+        iter
+    }
+
+    pub fn pre_order(&self) -> PreOrder<'_, T> {
+        PreOrder {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    pub fn post_order(&self) -> PostOrder<'_, T> {
+        PostOrder {
+            stack: self.root.as_deref().map(|node| (node, false)).into_iter().collect(),
+        }
+    }
+
+    pub fn level_order(&self) -> LevelOrder<'_, T> {
+        LevelOrder {
+            queue: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        // Walk the nodes directly rather than through `iter`, which trusts `len`.
+        let mut count = 0;
+        let mut previous: Option<&T> = None;
+        let mut stack = Vec::new();
+        let mut current = self.root.as_deref();
+        loop {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_deref();
+            }
+            let node = match stack.pop() {
+                Some(node) => node,
+                None => break,
+            };
+            if previous.is_some_and(|previous| *previous >= node.value) {
+                return Err(InvariantViolation::OutOfOrder);
             }
-            None => Some(Box::new(TreeNode {
-                value,
+            previous = Some(&node.value);
+            count += 1;
+            current = node.right.as_deref();
+        }
+        if count != self.len {
+            return Err(InvariantViolation::LengthMismatch);
+        }
+        Ok(())
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+}
+
+impl<T: Ord> Default for BinarySearchTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for BinarySearchTree<T> {
+    // Copy the tree iteratively, for the same reason `drop` is iterative.
+    fn clone(&self) -> Self {
+        let mut root = None;
+        let mut stack: Vec<(&TreeNode<T>, &mut Link<T>)> =
+            self.root.as_deref().map(|node| (node, &mut root)).into_iter().collect();
+        while let Some((source, target)) = stack.pop() {
+            let node = target.insert(Box::new(TreeNode {
+                value: source.value.clone(),
                 left: None,
                 right: None,
-            })),
+            }));
+            let TreeNode { left, right, .. } = &mut **node;
+            stack.extend(source.left.as_deref().map(|child| (child, left)));
+            stack.extend(source.right.as_deref().map(|child| (child, right)));
+        }
+        BinarySearchTree { root, len: self.len }
+    }
+}
+
+impl<T> Drop for BinarySearchTree<T> {
+    // Dismantle the tree iteratively, since the default recursive drop could
+    // overflow the stack on the long paths sorted input produces.
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<TreeNode<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
         }
     }
+}
 
-    pub fn contains(&self, value: &T) -> bool {
-        Self::contains_node(&self.root, value)
+pub struct Iter<'a, T> {
+    front: Vec<&'a TreeNode<T>>,
+    back: Vec<&'a TreeNode<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<T>>) {
+        while let Some(current) = node {
+            self.front.push(current);
+            node = current.left.as_deref();
+        }
     }
 
-    fn contains_node(node: &Option<Box<TreeNode<T>>>, value: &T) -> bool {
-        match node {
-            Some(node) => match value.cmp(&node.value) {
-                Ordering::Less => Self::contains_node(&node.left, value),
-                Ordering::Greater => Self::contains_node(&node.right, value),
-                Ordering::Equal => true,
-            },
-            None => false,
+    fn push_right(&mut self, mut node: Option<&'a TreeNode<T>>) {
+        while let Some(current) = node {
+            self.back.push(current);
+            node = current.right.as_deref();
         }
     }
+}
 
-    pub fn in_order_traversal(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        Self::in_order(&self.root, &mut result);
-        result
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+        Some(&node.value)
     }
 
-    fn in_order<'a>(node: &'a Option<Box<TreeNode<T>>>, result: &mut Vec<&'a T>) {
-        if let Some(node) = node {
-            Self::in_order(&node.left, result);
-            result.push(&node.value);
-            Self::in_order(&node.right, result);
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.push_right(node.left.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+pub struct IntoIter<T> {
+    inner: OwnedIter<TreeNode<T>>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> Open for TreeNode<T> {
+    type Item = T;
+
+    fn open(self: Box<Self>, parts: &mut Vec<Part<Self>>) {
+        let TreeNode { value, left, right } = *self;
+        parts.extend(left.map(Part::Node));
+        parts.push(Part::Value(value));
+        parts.extend(right.map(Part::Node));
+    }
+}
+
+pub struct PreOrder<'a, T> {
+    stack: Vec<&'a TreeNode<T>>,
+}
+
+impl<'a, T> Iterator for PreOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        Some(&node.value)
+    }
+}
+
+pub struct PostOrder<'a, T> {
+    // The flag records whether the node's children have already been pushed.
+    stack: Vec<(&'a TreeNode<T>, bool)>,
+}
+
+impl<'a, T> Iterator for PostOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(&node.value);
+            }
+            self.stack.push((node, true));
+            self.stack.extend(node.right.as_deref().map(|right| (right, false)));
+            self.stack.extend(node.left.as_deref().map(|left| (left, false)));
+        }
+    }
+}
+
+pub struct LevelOrder<'a, T> {
+    queue: VecDeque<&'a TreeNode<T>>,
+}
+
+impl<'a, T> Iterator for LevelOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.left.as_deref());
+        self.queue.extend(node.right.as_deref());
+        Some(&node.value)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a BinarySearchTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: Ord> IntoIterator for BinarySearchTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> IntoIter<T> {
+        // The tree's own drop must not see the nodes, so take the root out.
+        IntoIter {
+            inner: OwnedIter::new(self.root.take(), self.len),
         }
     }
 }
@@ -0,0 +1,28 @@
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use crate::utils::compare::{Compare, Natural};
+
+pub(crate) fn above_start<T: Ord>(start: Bound<&T>, value: &T) -> bool {
+    above_start_by(&Natural, start, value)
+}
+
+pub(crate) fn below_end<T: Ord>(end: Bound<&T>, value: &T) -> bool {
+    below_end_by(&Natural, end, value)
+}
+
+pub(crate) fn above_start_by<T, C: Compare<T>>(compare: &C, start: Bound<&T>, value: &T) -> bool {
+    match start {
+        Bound::Included(start) => compare.compare(value, start) != Ordering::Less,
+        Bound::Excluded(start) => compare.compare(value, start) == Ordering::Greater,
+        Bound::Unbounded => true,
+    }
+}
+
+pub(crate) fn below_end_by<T, C: Compare<T>>(compare: &C, end: Bound<&T>, value: &T) -> bool {
+    match end {
+        Bound::Included(end) => compare.compare(value, end) != Ordering::Greater,
+        Bound::Excluded(end) => compare.compare(value, end) == Ordering::Less,
+        Bound::Unbounded => true,
+    }
+}
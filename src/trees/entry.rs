@@ -0,0 +1,103 @@
+use super::map_tree::Position;
+
+// Implemented by `OrderedMap` so the entry can insert through it without
+// naming the map's tree type. `position` is where the map's search found the
+// key belongs, so inserting does not search again.
+pub(crate) trait VacantInsert<K, V> {
+    fn insert_vacant(&mut self, position: Position, key: K, value: V) -> &mut V;
+}
+
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    key: K,
+    value: &'a mut V,
+}
+
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    position: Position,
+    map: &'a mut dyn VacantInsert<K, V>,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    pub(crate) fn occupied(key: K, value: &'a mut V) -> Self {
+        Entry::Occupied(OccupiedEntry { key, value })
+    }
+
+    pub(crate) fn vacant(key: K, position: Position, map: &'a mut dyn VacantInsert<K, V>) -> Self {
+        Entry::Vacant(VacantEntry { key, position, map })
+    }
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(entry) => {
+                f(entry.value);
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K, V: Default> Entry<'a, K, V> {
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn get(&self) -> &V {
+        self.value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        self.value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.value, value)
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.insert_vacant(self.position, self.key, value)
+    }
+}
@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+use std::ops::{Bound, Range};
+
+use super::avl_tree::{AVLTree, Pruned};
+use super::bounds;
+use super::summary::Summary;
+use crate::utils::compare::Compare;
+
+// Half-open intervals with values, kept in an `AVLTree` ordered by
+// `(start, end)` whose summary is the largest `end` in each subtree, so
+// queries can skip subtrees that finish before the query begins. Bounds are
+// ordered by `Ord`; there is no comparator parameter.
+#[derive(Debug, Clone)]
+pub struct IntervalTree<K, V> {
+    tree: AVLTree<(Range<K>, V), MaxEnd<K>, ByInterval>,
+}
+
+// The largest interval end in a subtree.
+#[derive(Debug, Clone)]
+struct MaxEnd<K>(Option<K>);
+
+impl<K: Ord + Clone, V> Summary<(Range<K>, V)> for MaxEnd<K> {
+    fn empty() -> Self {
+        MaxEnd(None)
+    }
+
+    fn of((interval, _): &(Range<K>, V)) -> Self {
+        MaxEnd(Some(interval.end.clone()))
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        MaxEnd(self.0.clone().max(other.0.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ByInterval;
+
+impl<K: Ord, V> Compare<(Range<K>, V)> for ByInterval {
+    fn compare(&self, a: &(Range<K>, V), b: &(Range<K>, V)) -> Ordering {
+        order(&a.0, &b.0)
+    }
+}
+
+fn order<K: Ord>(a: &Range<K>, b: &Range<K>) -> Ordering {
+    a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end))
+}
+
+impl<K: Ord + Clone, V> IntervalTree<K, V> {
+    pub fn new() -> Self {
+        IntervalTree {
+            tree: AVLTree::with_summary_and_comparator(ByInterval),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn insert(&mut self, interval: Range<K>, value: V) -> Option<V> {
+        match self.tree.search_by(|(other, _)| order(&interval, other)) {
+            Ok(rank) => {
+                let (_, previous) = self.tree.select_mut(rank).unwrap();
+                Some(std::mem::replace(previous, value))
+            }
+            Err(rank) => {
+                self.tree.insert_at(rank, (interval, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, interval: &Range<K>) -> Option<V> {
+        self.tree.remove_by(|(other, _)| order(interval, other)).map(|(_, value)| value)
+    }
+
+    pub fn get(&self, interval: &Range<K>) -> Option<&V> {
+        self.tree.get_by(|(other, _)| order(interval, other)).map(|(_, value)| value)
+    }
+
+    // Yields every interval sharing at least one point with `range`, in
+    // order of `(start, end)`. An empty range or interval has no points, so
+    // it overlaps nothing.
+    pub fn overlapping(&self, range: Range<K>) -> Overlapping<'_, K, V> {
+        Overlapping::new(self.tree.pruned(), range.start, Bound::Excluded(range.end))
+    }
+
+    // Yields every interval containing `point`.
+    pub fn stabbing(&self, point: K) -> Overlapping<'_, K, V> {
+        Overlapping::new(self.tree.pruned(), point.clone(), Bound::Included(point))
+    }
+}
+
+impl<K: Ord + Clone, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// In-order walk over non-empty intervals with `start` within `high` and
+// `end > low`.
+pub struct Overlapping<'a, K, V> {
+    walk: Pruned<'a, (Range<K>, V), MaxEnd<K>>,
+    low: K,
+    high: Bound<K>,
+}
+
+impl<'a, K: Ord, V> Overlapping<'a, K, V> {
+    fn new(mut walk: Pruned<'a, (Range<K>, V), MaxEnd<K>>, low: K, high: Bound<K>) -> Self {
+        if !bounds::below_end(high.as_ref(), &low) {
+            walk.finish();
+        }
+        Overlapping { walk, low, high }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Overlapping<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<(&'a Range<K>, &'a V)> {
+        let low = &self.low;
+        // Subtrees whose intervals all end at or before `low` are skipped.
+        while let Some((interval, value)) = self.walk.next_by(|max| max.0.as_ref().is_some_and(|end| end > low)) {
+            // Intervals come out by increasing start, so once one starts past
+            // `high` none of the rest can overlap.
+            if !bounds::below_end(self.high.as_ref(), &interval.start) {
+                self.walk.finish();
+                return None;
+            }
+            if interval.end > *low && interval.start < interval.end {
+                return Some((interval, value));
+            }
+        }
+        None
+    }
+}
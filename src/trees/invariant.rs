@@ -0,0 +1,42 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation {
+    OutOfOrder,
+    LengthMismatch,
+    HeightMismatch,
+    Unbalanced,
+    SizeMismatch,
+    RedRoot,
+    RightLeaningRed,
+    ConsecutiveRed,
+    BlackHeightMismatch,
+    KeyCount,
+    ChildCount,
+    LeafDepthMismatch,
+    HeapOrder,
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            InvariantViolation::OutOfOrder => "keys are not in strictly increasing order",
+            InvariantViolation::LengthMismatch => "stored length does not match the number of keys",
+            InvariantViolation::HeightMismatch => "stored node height is incorrect",
+            InvariantViolation::Unbalanced => "node balance factor is outside [-1, 1]",
+            InvariantViolation::SizeMismatch => "stored subtree size is incorrect",
+            InvariantViolation::RedRoot => "root node is red",
+            InvariantViolation::RightLeaningRed => "red link leans right",
+            InvariantViolation::ConsecutiveRed => "red node has a red child",
+            InvariantViolation::BlackHeightMismatch => "paths have different black heights",
+            InvariantViolation::KeyCount => "node key count is outside the bounds for t",
+            InvariantViolation::ChildCount => "internal node child count does not match its keys",
+            InvariantViolation::LeafDepthMismatch => "leaves are at different depths",
+            InvariantViolation::HeapOrder => "node priority is below one of its children's",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for InvariantViolation {}
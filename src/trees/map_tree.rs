@@ -0,0 +1,43 @@
+use std::cmp::Ordering;
+
+use crate::utils::compare::Compare;
+
+// The items below are `pub` only so `OrderedMap`'s public signatures can name
+// them; this module is private, so nothing outside the crate can reach them.
+
+// A tree of key-value pairs ordered by key, as stored by an `OrderedMap`.
+// `search` reports where the tree's search found the key, or where it
+// belongs, so an entry can be inserted without searching again.
+pub trait MapTree {
+    type Key: Ord;
+    type Value;
+    type Iter<'a>: Iterator<Item = &'a (Self::Key, Self::Value)>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize;
+    fn search(&self, key: &Self::Key) -> Result<Position, Position>;
+    fn value_at(&mut self, position: &Position) -> &mut Self::Value;
+    fn insert_at(&mut self, position: Position, key: Self::Key, value: Self::Value) -> &mut Self::Value;
+    fn remove(&mut self, key: &Self::Key) -> Option<Self::Value>;
+    fn get(&self, key: &Self::Key) -> Option<&Self::Value>;
+    fn get_mut(&mut self, key: &Self::Key) -> Option<&mut Self::Value>;
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+// A key's place in a map's underlying tree: its in-order rank in a binary
+// tree, or its path of child and key indices in a B-tree.
+pub enum Position {
+    Rank(usize),
+    Path(Vec<usize>),
+}
+
+// Orders the maps' key-value pairs by key alone.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyOrder;
+
+impl<K: Ord, V> Compare<(K, V)> for KeyOrder {
+    fn compare(&self, a: &(K, V), b: &(K, V)) -> Ordering {
+        a.0.cmp(&b.0)
+    }
+}
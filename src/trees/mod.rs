@@ -1,4 +1,21 @@
 pub mod binary_search_tree;
+pub mod splay_tree;
+pub mod treap;
 pub mod avl_tree;
 pub mod red_black_tree;
+pub mod persistent_avl_tree;
+pub mod persistent_red_black_tree;
 pub mod b_tree;
+pub mod b_plus_tree;
+pub mod interval_tree;
+pub mod entry;
+pub mod invariant;
+pub mod summary;
+mod binary_node;
+mod bounds;
+mod map_tree;
+mod owned_iter;
+pub mod ordered_map;
+pub mod avl_map;
+pub mod red_black_map;
+pub mod b_tree_map;
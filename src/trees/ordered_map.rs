@@ -0,0 +1,67 @@
+use super::entry::{Entry, VacantInsert};
+use super::map_tree::{MapTree, Position};
+
+// An ordered map stored as key-value pairs in a tree compared by key. Keys
+// are ordered by `Ord`; unlike the trees, the maps take no comparator.
+// `AVLMap`, `RedBlackMap` and `BTreeOrderedMap` name it over their trees.
+#[derive(Debug, Clone)]
+pub struct OrderedMap<Tr> {
+    tree: Tr,
+}
+
+impl<Tr: MapTree> OrderedMap<Tr> {
+    pub(crate) fn from_tree(tree: Tr) -> Self {
+        OrderedMap { tree }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.len() == 0
+    }
+
+    pub fn insert(&mut self, key: Tr::Key, value: Tr::Value) -> Option<Tr::Value> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &Tr::Key) -> Option<Tr::Value> {
+        self.tree.remove(key)
+    }
+
+    pub fn get(&self, key: &Tr::Key) -> Option<&Tr::Value> {
+        self.tree.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &Tr::Key) -> Option<&mut Tr::Value> {
+        self.tree.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &Tr::Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<(&Tr::Key, &Tr::Value)> {
+        self.tree.iter().map(|(key, value)| (key, value)).collect()
+    }
+
+    pub fn entry(&mut self, key: Tr::Key) -> Entry<'_, Tr::Key, Tr::Value> {
+        match self.tree.search(&key) {
+            Ok(position) => Entry::occupied(key, self.tree.value_at(&position)),
+            Err(position) => Entry::vacant(key, position, self),
+        }
+    }
+}
+
+impl<Tr: MapTree> VacantInsert<Tr::Key, Tr::Value> for OrderedMap<Tr> {
+    fn insert_vacant(&mut self, position: Position, key: Tr::Key, value: Tr::Value) -> &mut Tr::Value {
+        self.tree.insert_at(position, key, value)
+    }
+}
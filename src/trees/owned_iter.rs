@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+
+// A tree node that an owning iterator can take apart. `open` consumes the
+// node and appends its children and values to `parts` in order.
+pub(crate) trait Open: Sized {
+    type Item;
+
+    fn open(self: Box<Self>, parts: &mut Vec<Part<Self>>);
+}
+
+pub(crate) enum Part<N: Open> {
+    Value(N::Item),
+    Node(Box<N>),
+}
+
+// The in-order iterator behind each tree's `into_iter`. A node is opened
+// only when one end of the iteration reaches it, so nodes are freed as the
+// walk passes them and the values are never gathered into a second buffer.
+pub(crate) struct OwnedIter<N: Open> {
+    parts: VecDeque<Part<N>>,
+    opened: Vec<Part<N>>,
+    len: usize,
+}
+
+impl<N: Open> OwnedIter<N> {
+    pub(crate) fn new(root: Option<Box<N>>, len: usize) -> Self {
+        OwnedIter {
+            parts: root.map(Part::Node).into_iter().collect(),
+            opened: Vec::new(),
+            len,
+        }
+    }
+}
+
+impl<N: Open> Iterator for OwnedIter<N> {
+    type Item = N::Item;
+
+    fn next(&mut self) -> Option<N::Item> {
+        loop {
+            match self.parts.pop_front()? {
+                Part::Value(value) => {
+                    self.len -= 1;
+                    return Some(value);
+                }
+                Part::Node(node) => {
+                    node.open(&mut self.opened);
+                    for part in self.opened.drain(..).rev() {
+                        self.parts.push_front(part);
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<N: Open> DoubleEndedIterator for OwnedIter<N> {
+    fn next_back(&mut self) -> Option<N::Item> {
+        loop {
+            match self.parts.pop_back()? {
+                Part::Value(value) => {
+                    self.len -= 1;
+                    return Some(value);
+                }
+                Part::Node(node) => {
+                    node.open(&mut self.opened);
+                    self.parts.extend(self.opened.drain(..));
+                }
+            }
+        }
+    }
+}
+
+impl<N: Open> ExactSizeIterator for OwnedIter<N> {}
+
+impl<N: Open> Drop for OwnedIter<N> {
+    // Keep opening nodes rather than dropping the remaining subtrees whole,
+    // whose recursive drop could overflow the stack on a degenerate tree.
+    fn drop(&mut self) {
+        while self.next().is_some() {}
+    }
+}
@@ -0,0 +1,295 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::invariant::InvariantViolation;
+
+type Link<T> = Option<Arc<TreeNode<T>>>;
+
+// Every version shares the subtrees it did not touch with the version it was
+// derived from. Updates copy only the nodes on the path from the root, so
+// cloning a tree or keeping old versions around is cheap.
+#[derive(Debug, Clone)]
+pub struct PersistentAVLTree<T> {
+    root: Link<T>,
+}
+
+#[derive(Debug, Clone)]
+struct TreeNode<T> {
+    value: T,
+    height: i32,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T: Ord + Clone> PersistentAVLTree<T> {
+    pub fn new() -> Self {
+        PersistentAVLTree { root: None }
+    }
+
+    pub fn insert(&self, value: T) -> Self {
+        if self.contains(&value) {
+            return self.clone();
+        }
+        PersistentAVLTree {
+            root: Some(Arc::new(Self::insert_node(self.root.clone(), value))),
+        }
+    }
+
+    // Takes the node out of `link`, copying it only if another version still
+    // refers to it.
+    fn take(link: &mut Link<T>) -> TreeNode<T> {
+        Arc::unwrap_or_clone(link.take().unwrap())
+    }
+
+    fn insert_node(mut node: Link<T>, value: T) -> TreeNode<T> {
+        if node.is_none() {
+            return TreeNode {
+                value,
+                height: 1,
+                size: 1,
+                left: None,
+                right: None,
+            };
+        }
+
+        let mut node = Self::take(&mut node);
+        if value < node.value {
+            node.left = Some(Arc::new(Self::insert_node(node.left.take(), value)));
+        } else {
+            node.right = Some(Arc::new(Self::insert_node(node.right.take(), value)));
+        }
+
+        Self::update(&mut node);
+        Self::balance(node)
+    }
+
+    pub fn remove(&self, value: &T) -> Self {
+        if !self.contains(value) {
+            return self.clone();
+        }
+        PersistentAVLTree {
+            root: Self::remove_node(self.root.clone(), value),
+        }
+    }
+
+    // Callers must ensure `value` is present in the subtree rooted at `node`.
+    fn remove_node(mut node: Link<T>, value: &T) -> Link<T> {
+        let mut node = Self::take(&mut node);
+
+        match value.cmp(&node.value) {
+            Ordering::Less => node.left = Self::remove_node(node.left.take(), value),
+            Ordering::Greater => node.right = Self::remove_node(node.right.take(), value),
+            Ordering::Equal => match (node.left.take(), node.right.take()) {
+                (None, right) => return right,
+                (left, None) => return left,
+                (left, Some(right)) => {
+                    // Replace the removed node with its in-order successor.
+                    let (right, mut successor) = Self::remove_min_node(Arc::unwrap_or_clone(right));
+                    successor.left = left;
+                    successor.right = right;
+                    node = successor;
+                }
+            },
+        }
+
+        Self::update(&mut node);
+        Some(Arc::new(Self::balance(node)))
+    }
+
+    fn remove_min_node(mut node: TreeNode<T>) -> (Link<T>, TreeNode<T>) {
+        match node.left.take() {
+            Some(left) => {
+                let (left, min) = Self::remove_min_node(Arc::unwrap_or_clone(left));
+                node.left = left;
+                Self::update(&mut node);
+                (Some(Arc::new(Self::balance(node))), min)
+            }
+            None => (node.right.take(), node),
+        }
+    }
+
+    fn update(node: &mut TreeNode<T>) {
+        node.height = 1 + i32::max(Self::height(&node.left), Self::height(&node.right));
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+    }
+
+    fn height(node: &Link<T>) -> i32 {
+        node.as_ref().map_or(0, |node| node.height)
+    }
+
+    fn size(node: &Link<T>) -> usize {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
+    fn balance_factor(node: &Link<T>) -> i32 {
+        node.as_ref().map_or(0, |node| Self::height(&node.left) - Self::height(&node.right))
+    }
+
+    fn balance(mut node: TreeNode<T>) -> TreeNode<T> {
+        let balance_factor = Self::height(&node.left) - Self::height(&node.right);
+
+        if balance_factor > 1 {
+            if Self::balance_factor(&node.left) < 0 {
+                node.left = Some(Arc::new(Self::rotate_left(Self::take(&mut node.left))));
+            }
+            return Self::rotate_right(node);
+        }
+
+        if balance_factor < -1 {
+            if Self::balance_factor(&node.right) > 0 {
+                node.right = Some(Arc::new(Self::rotate_right(Self::take(&mut node.right))));
+            }
+            return Self::rotate_left(node);
+        }
+
+        node
+    }
+
+    fn rotate_right(mut y: TreeNode<T>) -> TreeNode<T> {
+        let mut x = Self::take(&mut y.left);
+        y.left = x.right.take();
+        Self::update(&mut y);
+        x.right = Some(Arc::new(y));
+        Self::update(&mut x);
+        x
+    }
+
+    fn rotate_left(mut x: TreeNode<T>) -> TreeNode<T> {
+        let mut y = Self::take(&mut x.right);
+        x.right = y.left.take();
+        Self::update(&mut x);
+        y.left = Some(Arc::new(x));
+        Self::update(&mut y);
+        y
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.cmp(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => current = &node.right,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    // Counts the nodes of this version that are shared with `other` rather
+    // than copied, which shows how much two versions have in common.
+    pub fn shared_nodes(&self, other: &Self) -> usize {
+        let mut theirs = HashSet::new();
+        let mut stack: Vec<&Arc<TreeNode<T>>> = other.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            theirs.insert(Arc::as_ptr(node));
+            stack.extend(node.left.iter().chain(&node.right));
+        }
+
+        // A shared node brings its whole subtree with it.
+        let mut shared = 0;
+        let mut stack: Vec<&Arc<TreeNode<T>>> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            if theirs.contains(&Arc::as_ptr(node)) {
+                shared += node.size;
+            } else {
+                stack.extend(node.left.iter().chain(&node.right));
+            }
+        }
+        shared
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        Self::validate_node(&self.root, None, None).map(|_| ())
+    }
+
+    // Returns the height and size of a valid subtree whose values lie
+    // strictly between `lower` and `upper`.
+    fn validate_node(node: &Link<T>, lower: Option<&T>, upper: Option<&T>) -> Result<(i32, usize), InvariantViolation> {
+        let node = match node {
+            Some(node) => node,
+            None => return Ok((0, 0)),
+        };
+
+        if lower.is_some_and(|lower| node.value <= *lower) || upper.is_some_and(|upper| node.value >= *upper) {
+            return Err(InvariantViolation::OutOfOrder);
+        }
+        let (left_height, left_size) = Self::validate_node(&node.left, lower, Some(&node.value))?;
+        let (right_height, right_size) = Self::validate_node(&node.right, Some(&node.value), upper)?;
+
+        if node.height != 1 + i32::max(left_height, right_height) {
+            return Err(InvariantViolation::HeightMismatch);
+        }
+        if (left_height - right_height).abs() > 1 {
+            return Err(InvariantViolation::Unbalanced);
+        }
+        if node.size != 1 + left_size + right_size {
+            return Err(InvariantViolation::SizeMismatch);
+        }
+        Ok((node.height, node.size))
+    }
+}
+
+impl<T: Ord + Clone> Default for PersistentAVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Iter<'a, T> {
+    stack: Vec<&'a TreeNode<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T: Ord + Clone> IntoIterator for &'a PersistentAVLTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,357 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::invariant::InvariantViolation;
+
+type Link<T> = Option<Arc<TreeNode<T>>>;
+
+// A left-leaning red-black tree with the same path-copying scheme as
+// `PersistentAVLTree`: updates return a new version and leave `self` intact.
+#[derive(Debug, Clone)]
+pub struct PersistentRedBlackTree<T> {
+    root: Link<T>,
+}
+
+#[derive(Debug, Clone)]
+struct TreeNode<T> {
+    value: T,
+    color: Color,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Color {
+    Red,
+    Black,
+}
+
+impl<T: Ord + Clone> PersistentRedBlackTree<T> {
+    pub fn new() -> Self {
+        PersistentRedBlackTree { root: None }
+    }
+
+    pub fn insert(&self, value: T) -> Self {
+        if self.contains(&value) {
+            return self.clone();
+        }
+        let mut root = Self::insert_node(self.root.clone(), value);
+        root.color = Color::Black;
+        PersistentRedBlackTree {
+            root: Some(Arc::new(root)),
+        }
+    }
+
+    // Takes the node out of `link`, copying it only if another version still
+    // refers to it.
+    fn take(link: &mut Link<T>) -> TreeNode<T> {
+        Arc::unwrap_or_clone(link.take().unwrap())
+    }
+
+    fn insert_node(mut node: Link<T>, value: T) -> TreeNode<T> {
+        if node.is_none() {
+            return TreeNode {
+                value,
+                color: Color::Red,
+                size: 1,
+                left: None,
+                right: None,
+            };
+        }
+
+        let mut node = Self::take(&mut node);
+        if value < node.value {
+            node.left = Some(Arc::new(Self::insert_node(node.left.take(), value)));
+        } else {
+            node.right = Some(Arc::new(Self::insert_node(node.right.take(), value)));
+        }
+
+        Self::fix_up(node)
+    }
+
+    pub fn remove(&self, value: &T) -> Self {
+        if !self.contains(value) {
+            return self.clone();
+        }
+
+        let mut root = Self::take(&mut self.root.clone());
+        if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+            root.color = Color::Red;
+        }
+        let root = Self::remove_node(root, value).map(|mut root| {
+            root.color = Color::Black;
+            Arc::new(root)
+        });
+        PersistentRedBlackTree { root }
+    }
+
+    // Callers must ensure `value` is present in the subtree rooted at `node`.
+    fn remove_node(mut node: TreeNode<T>, value: &T) -> Option<TreeNode<T>> {
+        if *value < node.value {
+            if !Self::is_red(&node.left) && !Self::is_red_left_child(&node.left) {
+                node = Self::move_red_left(node);
+            }
+            node.left = Self::remove_node(Self::take(&mut node.left), value).map(Arc::new);
+        } else {
+            if Self::is_red(&node.left) {
+                node = Self::rotate_right(node);
+            }
+            if *value == node.value && node.right.is_none() {
+                return None;
+            }
+            if !Self::is_red(&node.right) && !Self::is_red_left_child(&node.right) {
+                node = Self::move_red_right(node);
+            }
+            if *value == node.value {
+                // Replace the removed value with its in-order successor.
+                let (right, min) = Self::remove_min_node(Self::take(&mut node.right));
+                node.right = right.map(Arc::new);
+                node.value = min.value;
+            } else {
+                node.right = Self::remove_node(Self::take(&mut node.right), value).map(Arc::new);
+            }
+        }
+
+        Some(Self::fix_up(node))
+    }
+
+    fn remove_min_node(mut node: TreeNode<T>) -> (Option<TreeNode<T>>, TreeNode<T>) {
+        if node.left.is_none() {
+            return (None, node);
+        }
+        if !Self::is_red(&node.left) && !Self::is_red_left_child(&node.left) {
+            node = Self::move_red_left(node);
+        }
+        let (left, min) = Self::remove_min_node(Self::take(&mut node.left));
+        node.left = left.map(Arc::new);
+        (Some(Self::fix_up(node)), min)
+    }
+
+    fn move_red_left(mut node: TreeNode<T>) -> TreeNode<T> {
+        Self::flip_colors(&mut node);
+        if Self::is_red_left_child(&node.right) {
+            node.right = Some(Arc::new(Self::rotate_right(Self::take(&mut node.right))));
+            node = Self::rotate_left(node);
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    fn move_red_right(mut node: TreeNode<T>) -> TreeNode<T> {
+        Self::flip_colors(&mut node);
+        if Self::is_red_left_child(&node.left) {
+            node = Self::rotate_right(node);
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    fn fix_up(mut node: TreeNode<T>) -> TreeNode<T> {
+        if Self::is_red(&node.right) && !Self::is_red(&node.left) {
+            node = Self::rotate_left(node);
+        }
+        if Self::is_red(&node.left) && Self::is_red_left_child(&node.left) {
+            node = Self::rotate_right(node);
+        }
+        if Self::is_red(&node.left) && Self::is_red(&node.right) {
+            Self::flip_colors(&mut node);
+        }
+        Self::update_size(&mut node);
+        node
+    }
+
+    fn is_red(node: &Link<T>) -> bool {
+        node.as_ref().is_some_and(|node| node.color == Color::Red)
+    }
+
+    fn is_red_left_child(node: &Link<T>) -> bool {
+        node.as_ref().is_some_and(|node| Self::is_red(&node.left))
+    }
+
+    fn rotate_left(mut node: TreeNode<T>) -> TreeNode<T> {
+        let mut x = Self::take(&mut node.right);
+        node.right = x.left.take();
+        x.color = node.color;
+        node.color = Color::Red;
+        Self::update_size(&mut node);
+        x.left = Some(Arc::new(node));
+        Self::update_size(&mut x);
+        x
+    }
+
+    fn rotate_right(mut node: TreeNode<T>) -> TreeNode<T> {
+        let mut x = Self::take(&mut node.left);
+        node.left = x.right.take();
+        x.color = node.color;
+        node.color = Color::Red;
+        Self::update_size(&mut node);
+        x.right = Some(Arc::new(node));
+        Self::update_size(&mut x);
+        x
+    }
+
+    fn flip(color: Color) -> Color {
+        match color {
+            Color::Red => Color::Black,
+            Color::Black => Color::Red,
+        }
+    }
+
+    // Recolouring a child copies it too if it is shared with another version.
+    fn flip_colors(node: &mut TreeNode<T>) {
+        node.color = Self::flip(node.color);
+        if let Some(left) = node.left.as_mut() {
+            let left = Arc::make_mut(left);
+            left.color = Self::flip(left.color);
+        }
+        if let Some(right) = node.right.as_mut() {
+            let right = Arc::make_mut(right);
+            right.color = Self::flip(right.color);
+        }
+    }
+
+    fn update_size(node: &mut TreeNode<T>) {
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+    }
+
+    fn size(node: &Link<T>) -> usize {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.cmp(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => current = &node.right,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    // Counts the nodes of this version that are shared with `other` rather
+    // than copied, which shows how much two versions have in common.
+    pub fn shared_nodes(&self, other: &Self) -> usize {
+        let mut theirs = HashSet::new();
+        let mut stack: Vec<&Arc<TreeNode<T>>> = other.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            theirs.insert(Arc::as_ptr(node));
+            stack.extend(node.left.iter().chain(&node.right));
+        }
+
+        // A shared node brings its whole subtree with it.
+        let mut shared = 0;
+        let mut stack: Vec<&Arc<TreeNode<T>>> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            if theirs.contains(&Arc::as_ptr(node)) {
+                shared += node.size;
+            } else {
+                stack.extend(node.left.iter().chain(&node.right));
+            }
+        }
+        shared
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        if Self::is_red(&self.root) {
+            return Err(InvariantViolation::RedRoot);
+        }
+        Self::validate_node(&self.root, None, None).map(|_| ())
+    }
+
+    // Returns the black height and size of a valid subtree whose values lie
+    // strictly between `lower` and `upper`.
+    fn validate_node(node: &Link<T>, lower: Option<&T>, upper: Option<&T>) -> Result<(usize, usize), InvariantViolation> {
+        let node = match node {
+            Some(node) => node,
+            None => return Ok((0, 0)),
+        };
+
+        if lower.is_some_and(|lower| node.value <= *lower) || upper.is_some_and(|upper| node.value >= *upper) {
+            return Err(InvariantViolation::OutOfOrder);
+        }
+        if Self::is_red(&node.right) {
+            return Err(InvariantViolation::RightLeaningRed);
+        }
+        if node.color == Color::Red && Self::is_red(&node.left) {
+            return Err(InvariantViolation::ConsecutiveRed);
+        }
+        let (left_height, left_size) = Self::validate_node(&node.left, lower, Some(&node.value))?;
+        let (right_height, right_size) = Self::validate_node(&node.right, Some(&node.value), upper)?;
+
+        if left_height != right_height {
+            return Err(InvariantViolation::BlackHeightMismatch);
+        }
+        if node.size != 1 + left_size + right_size {
+            return Err(InvariantViolation::SizeMismatch);
+        }
+        let height = if node.color == Color::Black { left_height + 1 } else { left_height };
+        Ok((height, node.size))
+    }
+}
+
+impl<T: Ord + Clone> Default for PersistentRedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Iter<'a, T> {
+    stack: Vec<&'a TreeNode<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T: Ord + Clone> IntoIterator for &'a PersistentRedBlackTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
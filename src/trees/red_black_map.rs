@@ -0,0 +1,65 @@
+use super::red_black_tree::{Iter, RedBlackTree};
+use super::map_tree::{KeyOrder, MapTree, Position};
+use super::ordered_map::OrderedMap;
+
+pub type RedBlackMap<K, V> = OrderedMap<RedBlackTree<(K, V), (), KeyOrder>>;
+
+impl<K: Ord, V> RedBlackMap<K, V> {
+    pub fn new() -> Self {
+        OrderedMap::from_tree(RedBlackTree::with_comparator(KeyOrder))
+    }
+}
+
+impl<K: Ord, V> Default for RedBlackMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> MapTree for RedBlackTree<(K, V), (), KeyOrder> {
+    type Key = K;
+    type Value = V;
+    type Iter<'a>
+        = Iter<'a, (K, V)>
+    where
+        Self: 'a;
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn search(&self, key: &K) -> Result<Position, Position> {
+        self.search_by(|(other, _)| key.cmp(other)).map(Position::Rank).map_err(Position::Rank)
+    }
+
+    fn value_at(&mut self, position: &Position) -> &mut V {
+        let Position::Rank(rank) = *position else {
+            unreachable!("binary tree maps only hand out ranks")
+        };
+        &mut self.select_mut(rank).unwrap().1
+    }
+
+    fn insert_at(&mut self, position: Position, key: K, value: V) -> &mut V {
+        let Position::Rank(rank) = position else {
+            unreachable!("binary tree maps only hand out ranks")
+        };
+        self.insert_at(rank, (key, value));
+        &mut self.select_mut(rank).unwrap().1
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove_by(|(other, _)| key.cmp(other)).map(|(_, value)| value)
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get_by(|(other, _)| key.cmp(other)).map(|(_, value)| value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_by_mut(|(other, _)| key.cmp(other)).map(|(_, value)| value)
+    }
+
+    fn iter(&self) -> Iter<'_, (K, V)> {
+        self.iter()
+    }
+}
@@ -1,16 +1,29 @@
 use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::ops::RangeBounds;
+
+use super::binary_node::{self, BinaryNode, Join};
+use super::invariant::InvariantViolation;
+use super::owned_iter::{Open, OwnedIter, Part};
+use super::summary::Summary;
+use crate::utils::compare::{Compare, Natural};
+
+type Link<T, S> = Option<Box<TreeNode<T, S>>>;
 
 #[derive(Debug, Clone)]
-pub struct RedBlackTree<T> {
-    root: Option<Box<TreeNode<T>>>,
+pub struct RedBlackTree<T, S = (), C = Natural> {
+    root: Link<T, S>,
+    compare: C,
 }
 
 #[derive(Debug, Clone)]
-struct TreeNode<T> {
+pub(crate) struct TreeNode<T, S> {
     value: T,
     color: Color,
-    left: Option<Box<TreeNode<T>>>,
-    right: Option<Box<TreeNode<T>>>,
+    size: usize,
+    summary: S,
+    left: Link<T, S>,
+    right: Link<T, S>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -21,34 +34,387 @@ enum Color {
 
 impl<T: Ord> RedBlackTree<T> {
     pub fn new() -> Self {
-        RedBlackTree { root: None }
+        RedBlackTree {
+            root: None,
+            compare: Natural,
+        }
+    }
+
+    // Builds a balanced tree in O(n) from values in ascending order.
+    // Repeated values are kept once; panics if the values are out of order.
+    pub fn from_sorted<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let mut values: Vec<T> = values.into_iter().collect();
+        values.dedup();
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]), "from_sorted requires values in ascending order");
+        let len = values.len();
+        // The tallest black height whose smallest tree still fits.
+        let black_height = (len + 1).ilog2();
+        RedBlackTree {
+            root: Self::build(&mut values.into_iter(), len, black_height),
+            compare: Natural,
+        }
+    }
+}
+
+impl<T: Ord> Default for RedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, S: Summary<T>> RedBlackTree<T, S> {
+    // Creates an empty tree that maintains a summary of type `S` over its
+    // values for `fold_range`.
+    pub fn with_summary() -> Self {
+        RedBlackTree {
+            root: None,
+            compare: Natural,
+        }
+    }
+}
+
+impl<T, C: Compare<T>> RedBlackTree<T, (), C> {
+    // Creates an empty tree ordered by `compare` instead of `Ord`.
+    pub fn with_comparator(compare: C) -> Self {
+        RedBlackTree { root: None, compare }
+    }
+}
+
+impl<T, S: Summary<T>, C: Compare<T>> RedBlackTree<T, S, C> {
+    // Creates an empty tree that both maintains a summary of type `S` and
+    // orders values by `compare`, combining `with_summary` and
+    // `with_comparator`.
+    pub fn with_summary_and_comparator(compare: C) -> Self {
+        RedBlackTree { root: None, compare }
     }
 
     pub fn insert(&mut self, value: T) {
-        self.root = Self::insert_node(self.root.take(), value);
-        if let Some(ref mut root) = self.root {
-            root.color = Color::Black;
+        if let Err(rank) = self.search_by(|other| self.compare.compare(&value, other)) {
+            self.insert_at(rank, value);
         }
     }
 
-    fn insert_node(node: Option<Box<TreeNode<T>>>, value: T) -> Option<Box<TreeNode<T>>> {
+    // Inserts `value` so that it becomes the value at in-order position
+    // `rank`. Callers pass the vacant position `search_by` reported.
+    pub(crate) fn insert_at(&mut self, rank: usize, value: T) {
+        self.root = Some(Self::insert_node(self.root.take(), rank, value));
+        Self::blacken(&mut self.root);
+    }
+
+    // Builds a subtree of `n` values with the given black height, which
+    // requires 2^h - 1 <= n <= 3^h - 1. Each black node is a 2-node, or a
+    // 3-node with a red left child, and the values are split evenly
+    // between its two or three subtrees.
+    fn build(values: &mut std::vec::IntoIter<T>, n: usize, black_height: u32) -> Link<T, S> {
+        if n == 0 {
+            return None;
+        }
+        let max_child = 3usize.pow(black_height - 1) - 1;
+        let (left, right) = if n - 1 <= 2 * max_child {
+            let left = Self::build(values, n - 1 - (n - 1) / 2, black_height - 1);
+            (left, (n - 1) / 2)
+        } else {
+            let rest = n - 2;
+            let first = Self::build(values, rest.div_ceil(3), black_height - 1);
+            let value = values.next().unwrap();
+            let second = Self::build(values, (rest + 1) / 3, black_height - 1);
+            (Some(Self::new_node(value, Color::Red, first, second)), rest / 3)
+        };
+        let value = values.next().unwrap();
+        let right = Self::build(values, right, black_height - 1);
+        Some(Self::new_node(value, Color::Black, left, right))
+    }
+
+    fn new_node(value: T, color: Color, left: Link<T, S>, right: Link<T, S>) -> Box<TreeNode<T, S>> {
+        let mut node = Box::new(TreeNode {
+            summary: S::of(&value),
+            value,
+            color,
+            size: 1,
+            left,
+            right,
+        });
+        Self::update(&mut node);
+        node
+    }
+
+    fn insert_node(node: Link<T, S>, rank: usize, value: T) -> Box<TreeNode<T, S>> {
         let mut node = match node {
             Some(node) => node,
-            None => return Some(Box::new(TreeNode {
-                value,
-                color: Color::Red,
-                left: None,
-                right: None,
-            })),
+            None => return Self::new_node(value, Color::Red, None, None),
         };
 
-        match value.cmp(&node.value) {
-            Ordering::Less => node.left = Self::insert_node(node.left.take(), value),
-            Ordering::Greater => node.right = Self::insert_node(node.right.take(), value),
-            Ordering::Equal => return Some(node),
+        let left_size = Self::size(&node.left);
+        if rank <= left_size {
+            node.left = Some(Self::insert_node(node.left.take(), rank, value));
+        } else {
+            node.right = Some(Self::insert_node(node.right.take(), rank - left_size - 1, value));
         }
 
-        // Fix the red-black tree properties
+        Self::fix_up(node)
+    }
+
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let compare = &self.compare;
+        Self::remove_from(&mut self.root, &mut |other: &T| compare.compare(value, other))
+    }
+
+    // Removes the value `probe` reports as equal. `probe` orders the target
+    // relative to the stored value it is given, like `search_by`.
+    pub(crate) fn remove_by<F: FnMut(&T) -> Ordering>(&mut self, mut probe: F) -> Option<T> {
+        Self::remove_from(&mut self.root, &mut probe)
+    }
+
+    fn remove_from<F: FnMut(&T) -> Ordering>(root: &mut Link<T, S>, probe: &mut F) -> Option<T> {
+        Self::find(root, probe)?;
+
+        let mut node = root.take().unwrap();
+        if !Self::is_red(&node.left) && !Self::is_red(&node.right) {
+            node.color = Color::Red;
+        }
+        let (node, removed) = Self::remove_node(node, probe);
+        *root = node;
+        Self::blacken(root);
+        Some(removed)
+    }
+
+    // Callers must ensure the value `probe` looks for is present in the
+    // subtree rooted at `node`.
+    fn remove_node<F: FnMut(&T) -> Ordering>(mut node: Box<TreeNode<T, S>>, probe: &mut F) -> (Link<T, S>, T) {
+        let removed;
+        if probe(&node.value) == Ordering::Less {
+            if !Self::is_red(&node.left) && !Self::is_red_left_child(&node.left) {
+                node = Self::move_red_left(node);
+            }
+            let (left, value) = Self::remove_node(node.left.take().unwrap(), probe);
+            node.left = left;
+            removed = value;
+        } else {
+            if Self::is_red(&node.left) {
+                node = Self::rotate_right(node);
+            }
+            if probe(&node.value) == Ordering::Equal && node.right.is_none() {
+                return (None, node.value);
+            }
+            if !Self::is_red(&node.right) && !Self::is_red_left_child(&node.right) {
+                node = Self::move_red_right(node);
+            }
+            if probe(&node.value) == Ordering::Equal {
+                // Replace the removed value with its in-order successor.
+                let (right, min) = Self::remove_min_node(node.right.take().unwrap());
+                node.right = right;
+                removed = std::mem::replace(&mut node.value, min.value);
+            } else {
+                let (right, value) = Self::remove_node(node.right.take().unwrap(), probe);
+                node.right = right;
+                removed = value;
+            }
+        }
+
+        (Some(Self::fix_up(node)), removed)
+    }
+
+    pub fn delete_min(&mut self) -> Option<T> {
+        let mut root = self.root.take()?;
+        if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+            root.color = Color::Red;
+        }
+        let (root, min) = Self::remove_min_node(root);
+        self.root = root;
+        if let Some(ref mut root) = self.root {
+            root.color = Color::Black;
+        }
+        Some(min.value)
+    }
+
+    fn remove_min_node(mut node: Box<TreeNode<T, S>>) -> (Link<T, S>, Box<TreeNode<T, S>>) {
+        if node.left.is_none() {
+            return (None, node);
+        }
+        if !Self::is_red(&node.left) && !Self::is_red_left_child(&node.left) {
+            node = Self::move_red_left(node);
+        }
+        let (left, min) = Self::remove_min_node(node.left.take().unwrap());
+        node.left = left;
+        (Some(Self::fix_up(node)), min)
+    }
+
+    pub fn delete_max(&mut self) -> Option<T> {
+        let mut root = self.root.take()?;
+        if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+            root.color = Color::Red;
+        }
+        let (root, max) = Self::remove_max_node(root);
+        self.root = root;
+        if let Some(ref mut root) = self.root {
+            root.color = Color::Black;
+        }
+        Some(max.value)
+    }
+
+    fn remove_max_node(mut node: Box<TreeNode<T, S>>) -> (Link<T, S>, Box<TreeNode<T, S>>) {
+        if Self::is_red(&node.left) {
+            node = Self::rotate_right(node);
+        }
+        if node.right.is_none() {
+            return (node.left.take(), node);
+        }
+        if !Self::is_red(&node.right) && !Self::is_red_left_child(&node.right) {
+            node = Self::move_red_right(node);
+        }
+        let (right, max) = Self::remove_max_node(node.right.take().unwrap());
+        node.right = right;
+        (Some(Self::fix_up(node)), max)
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        let root = Self::union_nodes(&self.compare, self.root, other.root);
+        Self::from_root(root, self.compare)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        let root = Self::intersection_nodes(&self.compare, self.root, other.root);
+        Self::from_root(root, self.compare)
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        let root = Self::difference_nodes(&self.compare, self.root, other.root);
+        Self::from_root(root, self.compare)
+    }
+
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        let root = Self::symmetric_difference_nodes(&self.compare, self.root, other.root);
+        Self::from_root(root, self.compare)
+    }
+
+    fn from_root(mut root: Link<T, S>, compare: C) -> Self {
+        Self::blacken(&mut root);
+        RedBlackTree { root, compare }
+    }
+
+    pub fn split_off(&mut self, value: &T) -> Self
+    where
+        C: Clone,
+    {
+        let (less, found, greater) = Self::split(&self.compare, self.root.take(), value);
+        self.root = less;
+        Self::blacken(&mut self.root);
+        let greater = match found {
+            Some(node) => Some(Self::join(None, node, greater)),
+            None => greater,
+        };
+        Self::from_root(greater, self.compare.clone())
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        // Concatenate directly when the key ranges do not overlap.
+        let before = matches!((self.iter().next_back(), other.iter().next()), (Some(a), Some(b)) if self.compare.compare(a, b) == Ordering::Less);
+        let after = matches!((other.iter().next_back(), self.iter().next()), (Some(a), Some(b)) if self.compare.compare(a, b) == Ordering::Less);
+        let (left, right) = (self.root.take(), other.root.take());
+        self.root = if before {
+            Self::join_pair(left, right)
+        } else if after {
+            Self::join_pair(right, left)
+        } else {
+            Self::union_nodes(&self.compare, left, right)
+        };
+        Self::blacken(&mut self.root);
+    }
+
+    fn join_right(node: Link<T, S>, mut mid: Box<TreeNode<T, S>>, right: Link<T, S>, height: usize, target: usize) -> Box<TreeNode<T, S>> {
+        if !Self::is_red(&node) && height == target {
+            mid.color = Color::Red;
+            mid.left = node;
+            mid.right = right;
+            Self::update(&mut mid);
+            return mid;
+        }
+
+        let mut node = node.unwrap();
+        let child_height = if node.color == Color::Black { height - 1 } else { height };
+        node.right = Some(Self::join_right(node.right.take(), mid, right, child_height, target));
+        Self::fix_up(node)
+    }
+
+    fn join_left(left: Link<T, S>, mut mid: Box<TreeNode<T, S>>, node: Link<T, S>, height: usize, target: usize) -> Box<TreeNode<T, S>> {
+        if !Self::is_red(&node) && height == target {
+            mid.color = Color::Red;
+            mid.left = left;
+            mid.right = node;
+            Self::update(&mut mid);
+            return mid;
+        }
+
+        let mut node = node.unwrap();
+        let child_height = if node.color == Color::Black { height - 1 } else { height };
+        node.left = Some(Self::join_left(left, mid, node.left.take(), child_height, target));
+        Self::fix_up(node)
+    }
+
+    fn update(node: &mut TreeNode<T, S>) {
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+        node.summary = Self::summarize(node);
+    }
+
+    fn summarize(node: &TreeNode<T, S>) -> S {
+        let summary = S::of(&node.value);
+        let summary = match &node.left {
+            Some(left) => left.summary.combine(&summary),
+            None => summary,
+        };
+        match &node.right {
+            Some(right) => summary.combine(&right.summary),
+            None => summary,
+        }
+    }
+
+    fn size(node: &Link<T, S>) -> usize {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
+    fn blacken(node: &mut Link<T, S>) {
+        if let Some(node) = node {
+            node.color = Color::Black;
+        }
+    }
+
+    fn black_height(node: &Link<T, S>) -> usize {
+        let mut height = 0;
+        let mut current = node;
+        while let Some(node) = current {
+            if node.color == Color::Black {
+                height += 1;
+            }
+            current = &node.left;
+        }
+        height
+    }
+
+    // Assuming `node` is red and both its children are black, make
+    // `node.left` or one of its children red.
+    fn move_red_left(mut node: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
+        Self::flip_colors(&mut node);
+        if Self::is_red_left_child(&node.right) {
+            node.right = Some(Self::rotate_right(node.right.take().unwrap()));
+            node = Self::rotate_left(node);
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    // Assuming `node` is red and both its children are black, make
+    // `node.right` or one of its children red.
+    fn move_red_right(mut node: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
+        Self::flip_colors(&mut node);
+        if Self::is_red_left_child(&node.left) {
+            node = Self::rotate_right(node);
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    // Fix the red-black tree properties
+    fn fix_up(mut node: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
         if Self::is_red(&node.right) && !Self::is_red(&node.left) {
             node = Self::rotate_left(node);
         }
@@ -58,36 +424,44 @@ impl<T: Ord> RedBlackTree<T> {
         if Self::is_red(&node.left) && Self::is_red(&node.right) {
             Self::flip_colors(&mut node);
         }
-
-        Some(node)
+        Self::update(&mut node);
+        node
     }
 
-    fn is_red(node: &Option<Box<TreeNode<T>>>) -> bool {
+    fn is_red(node: &Link<T, S>) -> bool {
         match node {
             Some(node) => node.color == Color::Red,
             None => false,
         }
     }
 
-    fn rotate_left(mut node: Box<TreeNode<T>>) -> Box<TreeNode<T>> {
+    fn is_red_left_child(node: &Link<T, S>) -> bool {
+        node.as_ref().is_some_and(|node| Self::is_red(&node.left))
+    }
+
+    fn rotate_left(mut node: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
         let mut x = node.right.take().unwrap();
         node.right = x.left.take();
         x.left = Some(node);
         x.color = x.left.as_ref().unwrap().color.clone();
         x.left.as_mut().unwrap().color = Color::Red;
+        Self::update(x.left.as_mut().unwrap());
+        Self::update(&mut x);
         x
     }
 
-    fn rotate_right(mut node: Box<TreeNode<T>>) -> Box<TreeNode<T>> {
+    fn rotate_right(mut node: Box<TreeNode<T, S>>) -> Box<TreeNode<T, S>> {
         let mut x = node.left.take().unwrap();
         node.left = x.right.take();
         x.right = Some(node);
         x.color = x.right.as_ref().unwrap().color.clone();
         x.right.as_mut().unwrap().color = Color::Red;
+        Self::update(x.right.as_mut().unwrap());
+        Self::update(&mut x);
         x
     }
 
-    fn flip_colors(node: &mut Box<TreeNode<T>>) {
+    fn flip_colors(node: &mut Box<TreeNode<T, S>>) {
         node.color = match node.color {
             Color::Red => Color::Black,
             Color::Black => Color::Red,
@@ -106,32 +480,419 @@ impl<T: Ord> RedBlackTree<T> {
         }
     }
 
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
     pub fn contains(&self, value: &T) -> bool {
-        Self::contains_node(&self.root, value)
+        Self::find(&self.root, &mut |other: &T| self.compare.compare(value, other)).is_some()
     }
 
-    fn contains_node(node: &Option<Box<TreeNode<T>>>, value: &T) -> bool {
-        match node {
-            Some(node) => match value.cmp(&node.value) {
-                Ordering::Less => Self::contains_node(&node.left, value),
-                Ordering::Greater => Self::contains_node(&node.right, value),
-                Ordering::Equal => true,
-            },
-            None => false,
+    // Finds the in-order position of the value `probe` reports as equal, or
+    // the position a new value would take. `probe` orders the target relative
+    // to the stored value it is given, so callers can search by part of a
+    // value, such as a map key.
+    pub(crate) fn search_by<F: FnMut(&T) -> Ordering>(&self, mut probe: F) -> Result<usize, usize> {
+        let mut current = &self.root;
+        let mut rank = 0;
+        while let Some(node) = current {
+            match probe(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => {
+                    rank += Self::size(&node.left) + 1;
+                    current = &node.right;
+                }
+                Ordering::Equal => return Ok(rank + Self::size(&node.left)),
+            }
+        }
+        Err(rank)
+    }
+
+    pub(crate) fn get_by<F: FnMut(&T) -> Ordering>(&self, mut probe: F) -> Option<&T> {
+        Self::find(&self.root, &mut probe).map(|node| &node.value)
+    }
+
+    // Callers must not change how the value orders or summarizes.
+    pub(crate) fn get_by_mut<F: FnMut(&T) -> Ordering>(&mut self, mut probe: F) -> Option<&mut T> {
+        let mut current = &mut self.root;
+        while let Some(node) = current {
+            match probe(&node.value) {
+                Ordering::Less => current = &mut node.left,
+                Ordering::Greater => current = &mut node.right,
+                Ordering::Equal => return Some(&mut node.value),
+            }
+        }
+        None
+    }
+
+    // Callers must not change how the value orders or summarizes.
+    pub(crate) fn select_mut(&mut self, k: usize) -> Option<&mut T> {
+        let mut current = &mut self.root;
+        let mut k = k;
+        while let Some(node) = current {
+            let left_size = Self::size(&node.left);
+            match k.cmp(&left_size) {
+                Ordering::Less => current = &mut node.left,
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    current = &mut node.right;
+                }
+            }
         }
+        None
+    }
+
+    fn find<'a, F: FnMut(&T) -> Ordering>(node: &'a Link<T, S>, probe: &mut F) -> Option<&'a TreeNode<T, S>> {
+        let mut current = node;
+        while let Some(node) = current {
+            match probe(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => current = &node.right,
+                Ordering::Equal => return Some(node),
+            }
+        }
+        None
+    }
+
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T, R, S, C> {
+        Range {
+            inner: binary_node::Range::new(self.root.as_deref(), range, &self.compare),
+        }
+    }
+
+    // Combines the summaries of every value in `range`, in ascending order.
+    pub fn fold_range<R: RangeBounds<T>>(&self, range: R) -> S {
+        binary_node::fold(&self.compare, self.root.as_deref(), &range, false, false)
+    }
+
+    pub fn floor(&self, value: &T) -> Option<&T> {
+        binary_node::floor(&self.compare, self.root.as_deref(), value, false)
+    }
+
+    pub fn ceiling(&self, value: &T) -> Option<&T> {
+        binary_node::ceiling(&self.compare, self.root.as_deref(), value, false)
+    }
+
+    pub fn predecessor(&self, value: &T) -> Option<&T> {
+        binary_node::floor(&self.compare, self.root.as_deref(), value, true)
+    }
+
+    pub fn successor(&self, value: &T) -> Option<&T> {
+        binary_node::ceiling(&self.compare, self.root.as_deref(), value, true)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter.push_right(self.root.as_deref());
+        iter
+    }
+
+    pub fn pre_order(&self) -> PreOrder<'_, T, S> {
+        PreOrder {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    pub fn post_order(&self) -> PostOrder<'_, T, S> {
+        PostOrder {
+            stack: self.root.as_deref().map(|node| (node, false)).into_iter().collect(),
+        }
+    }
+
+    pub fn level_order(&self) -> LevelOrder<'_, T, S> {
+        LevelOrder {
+            queue: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        if Self::is_red(&self.root) {
+            return Err(InvariantViolation::RedRoot);
+        }
+        Self::validate_node(&self.compare, &self.root, None, None).map(|_| ())
+    }
+
+    // Returns the black height and size of a valid subtree whose values lie
+    // strictly between `lower` and `upper`.
+    fn validate_node(compare: &C, node: &Link<T, S>, lower: Option<&T>, upper: Option<&T>) -> Result<(usize, usize), InvariantViolation> {
+        let node = match node {
+            Some(node) => node,
+            None => return Ok((0, 0)),
+        };
+
+        let below_lower = lower.is_some_and(|lower| compare.compare(&node.value, lower) != Ordering::Greater);
+        let above_upper = upper.is_some_and(|upper| compare.compare(&node.value, upper) != Ordering::Less);
+        if below_lower || above_upper {
+            return Err(InvariantViolation::OutOfOrder);
+        }
+        if Self::is_red(&node.right) {
+            return Err(InvariantViolation::RightLeaningRed);
+        }
+        if node.color == Color::Red && Self::is_red(&node.left) {
+            return Err(InvariantViolation::ConsecutiveRed);
+        }
+        let (left_height, left_size) = Self::validate_node(compare, &node.left, lower, Some(&node.value))?;
+        let (right_height, right_size) = Self::validate_node(compare, &node.right, Some(&node.value), upper)?;
+
+        if left_height != right_height {
+            return Err(InvariantViolation::BlackHeightMismatch);
+        }
+        if node.size != 1 + left_size + right_size {
+            return Err(InvariantViolation::SizeMismatch);
+        }
+        let height = if node.color == Color::Black { left_height + 1 } else { left_height };
+        Ok((height, node.size))
     }
 
     pub fn in_order_traversal(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        Self::in_order(&self.root, &mut result);
-        result
+        self.iter().collect()
     }
+}
 
-    fn in_order<'a>(node: &'a Option<Box<TreeNode<T>>>, result: &mut Vec<&'a T>) {
-        if let Some(node) = node {
-            Self::in_order(&node.left, result);
-            result.push(&node.value);
-            Self::in_order(&node.right, result);
+impl<T, S: Summary<T>, C: Compare<T>> Join for RedBlackTree<T, S, C> {
+    type Node = TreeNode<T, S>;
+
+    // Hangs the middle node as a red link at the matching black height and
+    // repairs the path above it exactly as after an insertion. The halves of
+    // a split may have red roots, so both sides are blackened first.
+    fn join(mut left: Link<T, S>, mut mid: Box<TreeNode<T, S>>, mut right: Link<T, S>) -> Box<TreeNode<T, S>> {
+        Self::blacken(&mut left);
+        Self::blacken(&mut right);
+        let (left_height, right_height) = (Self::black_height(&left), Self::black_height(&right));
+
+        let mut root = match left_height.cmp(&right_height) {
+            Ordering::Equal => {
+                mid.left = left;
+                mid.right = right;
+                Self::update(&mut mid);
+                mid
+            }
+            Ordering::Greater => Self::join_right(left, mid, right, left_height, right_height),
+            Ordering::Less => Self::join_left(left, mid, right, right_height, left_height),
+        };
+        root.color = Color::Black;
+        root
+    }
+
+    fn join_pair(left: Link<T, S>, right: Link<T, S>) -> Link<T, S> {
+        let mut left = match left {
+            Some(left) => left,
+            None => return right,
+        };
+
+        left.color = Color::Black;
+        if !Self::is_red(&left.left) && !Self::is_red(&left.right) {
+            left.color = Color::Red;
+        }
+        let (left, max) = Self::remove_max_node(left);
+        Some(Self::join(left, max, right))
+    }
+}
+
+pub struct Range<'a, T, R, S = (), C = Natural> {
+    inner: binary_node::Range<'a, TreeNode<T, S>, R, C>,
+}
+
+impl<'a, T, R: RangeBounds<T>, S, C: Compare<T>> Iterator for Range<'a, T, R, S, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.inner.next()
+    }
+}
+
+pub struct Iter<'a, T, S = ()> {
+    front: Vec<&'a TreeNode<T, S>>,
+    back: Vec<&'a TreeNode<T, S>>,
+    len: usize,
+}
+
+impl<'a, T, S> Iter<'a, T, S> {
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<T, S>>) {
+        while let Some(current) = node {
+            self.front.push(current);
+            node = current.left.as_deref();
+        }
+    }
+
+    fn push_right(&mut self, mut node: Option<&'a TreeNode<T, S>>) {
+        while let Some(current) = node {
+            self.back.push(current);
+            node = current.right.as_deref();
+        }
+    }
+}
+
+impl<'a, T, S> Iterator for Iter<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.front.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, S> DoubleEndedIterator for Iter<'a, T, S> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.back.pop()?;
+        self.push_right(node.left.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+}
+
+impl<'a, T, S> ExactSizeIterator for Iter<'a, T, S> {}
+
+pub struct IntoIter<T, S = ()> {
+    inner: OwnedIter<TreeNode<T, S>>,
+}
+
+impl<T, S> Iterator for IntoIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for IntoIter<T, S> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, S> ExactSizeIterator for IntoIter<T, S> {}
+
+impl<T, S> BinaryNode for TreeNode<T, S> {
+    type Value = T;
+    type Summary = S;
+
+    fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn summary(&self) -> &S {
+        &self.summary
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+
+    fn take_children(&mut self) -> (Link<T, S>, Link<T, S>) {
+        (self.left.take(), self.right.take())
+    }
+}
+
+impl<T, S> Open for TreeNode<T, S> {
+    type Item = T;
+
+    fn open(self: Box<Self>, parts: &mut Vec<Part<Self>>) {
+        let TreeNode { value, left, right, .. } = *self;
+        parts.extend(left.map(Part::Node));
+        parts.push(Part::Value(value));
+        parts.extend(right.map(Part::Node));
+    }
+}
+
+pub struct PreOrder<'a, T, S = ()> {
+    stack: Vec<&'a TreeNode<T, S>>,
+}
+
+impl<'a, T, S> Iterator for PreOrder<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        Some(&node.value)
+    }
+}
+
+pub struct PostOrder<'a, T, S = ()> {
+    // The flag records whether the node's children have already been pushed.
+    stack: Vec<(&'a TreeNode<T, S>, bool)>,
+}
+
+impl<'a, T, S> Iterator for PostOrder<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(&node.value);
+            }
+            self.stack.push((node, true));
+            self.stack.extend(node.right.as_deref().map(|right| (right, false)));
+            self.stack.extend(node.left.as_deref().map(|left| (left, false)));
+        }
+    }
+}
+
+pub struct LevelOrder<'a, T, S = ()> {
+    queue: VecDeque<&'a TreeNode<T, S>>,
+}
+
+impl<'a, T, S> Iterator for LevelOrder<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.left.as_deref());
+        self.queue.extend(node.right.as_deref());
+        Some(&node.value)
+    }
+}
+
+impl<'a, T, S: Summary<T>, C: Compare<T>> IntoIterator for &'a RedBlackTree<T, S, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, S>;
+
+    fn into_iter(self) -> Iter<'a, T, S> {
+        self.iter()
+    }
+}
+
+impl<T, S: Summary<T>, C: Compare<T>> IntoIterator for RedBlackTree<T, S, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T, S>;
+
+    fn into_iter(self) -> IntoIter<T, S> {
+        let len = self.len();
+        IntoIter {
+            inner: OwnedIter::new(self.root, len),
         }
     }
 }
@@ -0,0 +1,333 @@
+use std::cmp::Ordering;
+
+use super::invariant::InvariantViolation;
+
+type Link<T> = Option<Box<TreeNode<T>>>;
+
+// A self-adjusting binary search tree. Every access splays the node it
+// reaches to the root, so recently and frequently used values stay near the
+// top and each operation is O(log n) amortized.
+#[derive(Debug)]
+pub struct SplayTree<T> {
+    root: Link<T>,
+}
+
+#[derive(Debug)]
+struct TreeNode<T> {
+    value: T,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T: Ord> SplayTree<T> {
+    pub fn new() -> Self {
+        SplayTree { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn insert(&mut self, value: T) {
+        let mut root = match self.root.take() {
+            Some(root) => Self::splay(root, |node| value.cmp(node)),
+            None => {
+                self.root = Some(Self::new_node(value));
+                return;
+            }
+        };
+
+        let mut node = Self::new_node(value);
+        match node.value.cmp(&root.value) {
+            Ordering::Less => {
+                node.left = root.left.take();
+                Self::update(&mut root);
+                node.right = Some(root);
+            }
+            Ordering::Greater => {
+                node.right = root.right.take();
+                Self::update(&mut root);
+                node.left = Some(root);
+            }
+            Ordering::Equal => {
+                self.root = Some(root);
+                return;
+            }
+        }
+        Self::update(&mut node);
+        self.root = Some(node);
+    }
+
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let mut root = Self::splay(self.root.take()?, |node| value.cmp(node));
+        if root.value != *value {
+            self.root = Some(root);
+            return None;
+        }
+
+        self.root = match (root.left.take(), root.right.take()) {
+            (None, right) => right,
+            (Some(left), right) => {
+                // Splaying the maximum of the left subtree to its root leaves
+                // it with no right child to make room for `right`.
+                let mut left = Self::splay(left, |_| Ordering::Greater);
+                left.right = right;
+                Self::update(&mut left);
+                Some(left)
+            }
+        };
+        Some(root.value)
+    }
+
+    pub fn contains(&mut self, value: &T) -> bool {
+        match self.root.take() {
+            Some(root) => {
+                let root = Self::splay(root, |node| value.cmp(node));
+                let found = root.value == *value;
+                self.root = Some(root);
+                found
+            }
+            None => false,
+        }
+    }
+
+    // Moves every value greater than or equal to `value` into the returned
+    // tree, leaving the smaller ones in `self`.
+    pub fn split(&mut self, value: &T) -> Self {
+        let mut root = match self.root.take() {
+            Some(root) => Self::splay(root, |node| value.cmp(node)),
+            None => return SplayTree::new(),
+        };
+
+        if root.value >= *value {
+            self.root = root.left.take();
+            Self::update(&mut root);
+            SplayTree { root: Some(root) }
+        } else {
+            let greater = root.right.take();
+            Self::update(&mut root);
+            self.root = Some(root);
+            SplayTree { root: greater }
+        }
+    }
+
+    // Appends `other`, whose values must all be greater than those in `self`.
+    pub fn join(&mut self, mut other: Self) {
+        let other = match other.root.take() {
+            Some(root) => Self::splay(root, |_| Ordering::Less),
+            None => return,
+        };
+
+        let mut root = match self.root.take() {
+            Some(root) => Self::splay(root, |_| Ordering::Greater),
+            None => {
+                self.root = Some(other);
+                return;
+            }
+        };
+        assert!(root.value < other.value, "join requires every value in `other` to be greater than those in `self`");
+        root.right = Some(other);
+        Self::update(&mut root);
+        self.root = Some(root);
+    }
+
+    fn new_node(value: T) -> Box<TreeNode<T>> {
+        Box::new(TreeNode {
+            value,
+            size: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    // Top-down splay: walks from the root towards the target, detaching the
+    // nodes it passes into a tree of smaller and a tree of larger values,
+    // then hangs those off the last node reached. `compare` orders the
+    // target against a node's value, so callers can also splay the minimum
+    // or maximum without naming a value.
+    fn splay<F: FnMut(&T) -> Ordering>(mut root: Box<TreeNode<T>>, mut compare: F) -> Box<TreeNode<T>> {
+        // Each smaller node still needs its right child and each larger node
+        // its left child; both are filled in when the trees are reassembled.
+        let mut smaller = Vec::new();
+        let mut larger = Vec::new();
+
+        loop {
+            match compare(&root.value) {
+                Ordering::Less => {
+                    let mut child = match root.left.take() {
+                        Some(child) => child,
+                        None => break,
+                    };
+                    if compare(&child.value) == Ordering::Less {
+                        // Zig-zig: rotate right before linking.
+                        root.left = child.right.take();
+                        Self::update(&mut root);
+                        child.right = Some(root);
+                        root = child;
+                        match root.left.take() {
+                            Some(left) => {
+                                larger.push(root);
+                                root = left;
+                            }
+                            None => break,
+                        }
+                    } else {
+                        larger.push(root);
+                        root = child;
+                    }
+                }
+                Ordering::Greater => {
+                    let mut child = match root.right.take() {
+                        Some(child) => child,
+                        None => break,
+                    };
+                    if compare(&child.value) == Ordering::Greater {
+                        // Zag-zag: rotate left before linking.
+                        root.right = child.left.take();
+                        Self::update(&mut root);
+                        child.left = Some(root);
+                        root = child;
+                        match root.right.take() {
+                            Some(right) => {
+                                smaller.push(root);
+                                root = right;
+                            }
+                            None => break,
+                        }
+                    } else {
+                        smaller.push(root);
+                        root = child;
+                    }
+                }
+                Ordering::Equal => break,
+            }
+        }
+
+        let mut left = root.left.take();
+        while let Some(mut node) = smaller.pop() {
+            node.right = left;
+            Self::update(&mut node);
+            left = Some(node);
+        }
+        let mut right = root.right.take();
+        while let Some(mut node) = larger.pop() {
+            node.left = right;
+            Self::update(&mut node);
+            right = Some(node);
+        }
+        root.left = left;
+        root.right = right;
+        Self::update(&mut root);
+        root
+    }
+
+    fn update(node: &mut TreeNode<T>) {
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+    }
+
+    fn size(node: &Link<T>) -> usize {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        // Walk iteratively; splaying can leave paths far too deep to recurse.
+        let mut previous: Option<&T> = None;
+        let mut stack = Vec::new();
+        let mut current = self.root.as_deref();
+        loop {
+            while let Some(node) = current {
+                stack.push(node);
+                current = node.left.as_deref();
+            }
+            let node = match stack.pop() {
+                Some(node) => node,
+                None => break,
+            };
+            if previous.is_some_and(|previous| *previous >= node.value) {
+                return Err(InvariantViolation::OutOfOrder);
+            }
+            if node.size != 1 + Self::size(&node.left) + Self::size(&node.right) {
+                return Err(InvariantViolation::SizeMismatch);
+            }
+            previous = Some(&node.value);
+            current = node.right.as_deref();
+        }
+        Ok(())
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+}
+
+impl<T: Ord> Default for SplayTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for SplayTree<T> {
+    // Dismantle the tree iteratively, since the default recursive drop could
+    // overflow the stack on the long paths splaying produces.
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<TreeNode<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    stack: Vec<&'a TreeNode<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T: Ord> IntoIterator for &'a SplayTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,23 @@
+// A monoid summary that `AVLTree` and `RedBlackTree` can keep on every node.
+//
+// Each node stores the combination of the summaries of every value in its
+// subtree, recomputed whenever the node is restructured, which lets
+// `fold_range` combine any range of values in O(log n). `combine` must be
+// associative and `empty` must be its identity; it need not be commutative,
+// since values are always combined in ascending order.
+pub trait Summary<T>: Clone {
+    fn empty() -> Self;
+
+    fn of(value: &T) -> Self;
+
+    fn combine(&self, other: &Self) -> Self;
+}
+
+// The summary used by trees that do not need one.
+impl<T> Summary<T> for () {
+    fn empty() -> Self {}
+
+    fn of(_: &T) -> Self {}
+
+    fn combine(&self, _: &Self) -> Self {}
+}
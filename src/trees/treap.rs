@@ -0,0 +1,596 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::ops::{Bound, RangeBounds};
+
+use super::invariant::InvariantViolation;
+
+// A binary search tree on values that is simultaneously a max-heap on
+// randomly drawn priorities, which keeps its expected depth at O(log n).
+// Everything is built from `split` and `merge`.
+#[derive(Debug, Clone)]
+pub struct Treap<T> {
+    root: Link<T>,
+    rng: XorShift,
+}
+
+type Link<T> = Option<Box<TreeNode<T>>>;
+
+#[derive(Debug, Clone)]
+struct TreeNode<T> {
+    value: T,
+    priority: u64,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T: Ord> Treap<T> {
+    pub fn new() -> Self {
+        Treap {
+            root: None,
+            rng: XorShift::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn insert(&mut self, value: T) {
+        if self.contains(&value) {
+            return;
+        }
+        let node = Box::new(TreeNode {
+            value,
+            priority: self.rng.next(),
+            size: 1,
+            left: None,
+            right: None,
+        });
+        self.root = Some(Self::insert_node(self.root.take(), node));
+    }
+
+    // Walks down until the new node outranks the subtree it lands on, then
+    // splits that subtree around it.
+    fn insert_node(node: Link<T>, mut new: Box<TreeNode<T>>) -> Box<TreeNode<T>> {
+        let mut node = match node {
+            Some(node) if node.priority >= new.priority => node,
+            node => {
+                let (left, right) = Self::split_node(node, &new.value);
+                new.left = left;
+                new.right = right;
+                Self::update(&mut new);
+                return new;
+            }
+        };
+
+        if new.value < node.value {
+            node.left = Some(Self::insert_node(node.left.take(), new));
+        } else {
+            node.right = Some(Self::insert_node(node.right.take(), new));
+        }
+        Self::update(&mut node);
+        node
+    }
+
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let (root, removed) = Self::remove_node(self.root.take(), value);
+        self.root = root;
+        removed
+    }
+
+    fn remove_node(node: Link<T>, value: &T) -> (Link<T>, Option<T>) {
+        let mut node = match node {
+            Some(node) => node,
+            None => return (None, None),
+        };
+
+        let removed = match value.cmp(&node.value) {
+            Ordering::Less => {
+                let (left, removed) = Self::remove_node(node.left.take(), value);
+                node.left = left;
+                removed
+            }
+            Ordering::Greater => {
+                let (right, removed) = Self::remove_node(node.right.take(), value);
+                node.right = right;
+                removed
+            }
+            Ordering::Equal => {
+                let merged = Self::merge_nodes(node.left.take(), node.right.take());
+                return (merged, Some(node.value));
+            }
+        };
+        Self::update(&mut node);
+        (Some(node), removed)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.cmp(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => current = &node.right,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    // Moves every value greater than or equal to `value` into the returned
+    // treap, leaving the smaller ones in `self`.
+    pub fn split(&mut self, value: &T) -> Self {
+        let (less, greater) = Self::split_node(self.root.take(), value);
+        self.root = less;
+        Treap {
+            root: greater,
+            rng: self.rng.fork(),
+        }
+    }
+
+    // Appends `other`, whose values must all be greater than those in `self`.
+    pub fn merge(&mut self, mut other: Self) {
+        let max = Self::extreme(&self.root, |node| &node.right);
+        let min = Self::extreme(&other.root, |node| &node.left);
+        if let (Some(max), Some(min)) = (max, min) {
+            assert!(max < min, "merge requires every value in `other` to be greater than those in `self`");
+        }
+        self.root = Self::merge_nodes(self.root.take(), other.root.take());
+    }
+
+    // Follows `next` from the root as far as it goes.
+    fn extreme<'a>(node: &'a Link<T>, next: impl Fn(&'a TreeNode<T>) -> &'a Link<T>) -> Option<&'a T> {
+        let mut node = node.as_deref()?;
+        while let Some(child) = next(node).as_deref() {
+            node = child;
+        }
+        Some(&node.value)
+    }
+
+    fn split_node(node: Link<T>, value: &T) -> (Link<T>, Link<T>) {
+        let mut node = match node {
+            Some(node) => node,
+            None => return (None, None),
+        };
+
+        if node.value < *value {
+            let (less, greater) = Self::split_node(node.right.take(), value);
+            node.right = less;
+            Self::update(&mut node);
+            (Some(node), greater)
+        } else {
+            let (less, greater) = Self::split_node(node.left.take(), value);
+            node.left = greater;
+            Self::update(&mut node);
+            (less, Some(node))
+        }
+    }
+
+    // Every value in `left` must be smaller than every value in `right`.
+    fn merge_nodes(left: Link<T>, right: Link<T>) -> Link<T> {
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(mut left), Some(mut right)) => {
+                if left.priority >= right.priority {
+                    left.right = Self::merge_nodes(left.right.take(), Some(right));
+                    Self::update(&mut left);
+                    Some(left)
+                } else {
+                    right.left = Self::merge_nodes(Some(left), right.left.take());
+                    Self::update(&mut right);
+                    Some(right)
+                }
+            }
+        }
+    }
+
+    fn update(node: &mut TreeNode<T>) {
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+    }
+
+    fn size(node: &Link<T>) -> usize {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        Self::validate_node(&self.root, None, None).map(|_| ())
+    }
+
+    // Returns the size of a valid subtree whose values lie strictly between
+    // `lower` and `upper`.
+    fn validate_node(node: &Link<T>, lower: Option<&T>, upper: Option<&T>) -> Result<usize, InvariantViolation> {
+        let node = match node {
+            Some(node) => node,
+            None => return Ok(0),
+        };
+
+        if lower.is_some_and(|lower| node.value <= *lower) || upper.is_some_and(|upper| node.value >= *upper) {
+            return Err(InvariantViolation::OutOfOrder);
+        }
+        if [&node.left, &node.right].into_iter().flatten().any(|child| child.priority > node.priority) {
+            return Err(InvariantViolation::HeapOrder);
+        }
+        let left_size = Self::validate_node(&node.left, lower, Some(&node.value))?;
+        let right_size = Self::validate_node(&node.right, Some(&node.value), upper)?;
+        if node.size != 1 + left_size + right_size {
+            return Err(InvariantViolation::SizeMismatch);
+        }
+        Ok(node.size)
+    }
+
+    pub fn in_order_traversal(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+}
+
+impl<T: Ord> Default for Treap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Iter<'a, T> {
+    stack: Vec<&'a TreeNode<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T: Ord> IntoIterator for &'a Treap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+// A treap keyed by position rather than value: a node's index is the size
+// of everything to its left, so it behaves as a sequence with O(log n)
+// expected insertion, removal, splitting and concatenation anywhere.
+// Reversing a range only flags the root of that range; the flag is pushed
+// down to the children the next time the node is restructured.
+#[derive(Debug, Clone)]
+pub struct ImplicitTreap<T> {
+    root: SeqLink<T>,
+    rng: XorShift,
+}
+
+type SeqLink<T> = Option<Box<SeqNode<T>>>;
+
+#[derive(Debug, Clone)]
+struct SeqNode<T> {
+    value: T,
+    priority: u64,
+    size: usize,
+    reversed: bool,
+    left: SeqLink<T>,
+    right: SeqLink<T>,
+}
+
+impl<T> ImplicitTreap<T> {
+    pub fn new() -> Self {
+        ImplicitTreap {
+            root: None,
+            rng: XorShift::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.insert(self.len(), value);
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len(), "insertion index {} is out of bounds for length {}", index, self.len());
+        let node = Box::new(SeqNode {
+            value,
+            priority: self.rng.next(),
+            size: 1,
+            reversed: false,
+            left: None,
+            right: None,
+        });
+        let (left, right) = Self::split_node(self.root.take(), index);
+        self.root = Self::merge_nodes(Self::merge_nodes(left, Some(node)), right);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let (left, rest) = Self::split_node(self.root.take(), index);
+        let (node, right) = Self::split_node(rest, 1);
+        self.root = Self::merge_nodes(left, right);
+        node.map(|node| node.value)
+    }
+
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        let mut current = self.root.as_deref();
+        // Whether an odd number of reversals are pending above `current`.
+        let mut flipped = false;
+        while let Some(node) = current {
+            flipped ^= node.reversed;
+            let (left, right) = node.children(flipped);
+            let left_size = left.map_or(0, |left| left.size);
+            match index.cmp(&left_size) {
+                Ordering::Less => current = left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    current = right;
+                }
+            }
+        }
+        None
+    }
+
+    pub fn reverse<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = self.bounds(range);
+        let (left, rest) = Self::split_node(self.root.take(), start);
+        let (mut middle, right) = Self::split_node(rest, end - start);
+        if let Some(middle) = middle.as_mut() {
+            middle.reversed ^= true;
+        }
+        self.root = Self::merge_nodes(Self::merge_nodes(left, middle), right);
+    }
+
+    // Moves the elements from `at` onwards into the returned sequence.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "split index {} is out of bounds for length {}", at, self.len());
+        let (left, right) = Self::split_node(self.root.take(), at);
+        self.root = left;
+        ImplicitTreap {
+            root: right,
+            rng: self.rng.fork(),
+        }
+    }
+
+    // Concatenates `other` onto the end of `self`, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        self.root = Self::merge_nodes(self.root.take(), other.root.take());
+    }
+
+    fn bounds<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(start <= end && end <= self.len(), "range {}..{} is out of bounds for length {}", start, end, self.len());
+        (start, end)
+    }
+
+    // Splits off the first `count` elements.
+    fn split_node(node: SeqLink<T>, count: usize) -> (SeqLink<T>, SeqLink<T>) {
+        let mut node = match node {
+            Some(node) => node,
+            None => return (None, None),
+        };
+
+        Self::push_down(&mut node);
+        let left_size = Self::size(&node.left);
+        if count <= left_size {
+            let (left, right) = Self::split_node(node.left.take(), count);
+            node.left = right;
+            Self::update(&mut node);
+            (left, Some(node))
+        } else {
+            let (left, right) = Self::split_node(node.right.take(), count - left_size - 1);
+            node.right = left;
+            Self::update(&mut node);
+            (Some(node), right)
+        }
+    }
+
+    fn merge_nodes(left: SeqLink<T>, right: SeqLink<T>) -> SeqLink<T> {
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(mut left), Some(mut right)) => {
+                if left.priority >= right.priority {
+                    Self::push_down(&mut left);
+                    left.right = Self::merge_nodes(left.right.take(), Some(right));
+                    Self::update(&mut left);
+                    Some(left)
+                } else {
+                    Self::push_down(&mut right);
+                    right.left = Self::merge_nodes(Some(left), right.left.take());
+                    Self::update(&mut right);
+                    Some(right)
+                }
+            }
+        }
+    }
+
+    fn push_down(node: &mut SeqNode<T>) {
+        if node.reversed {
+            node.reversed = false;
+            std::mem::swap(&mut node.left, &mut node.right);
+            for child in [&mut node.left, &mut node.right].into_iter().flatten() {
+                child.reversed ^= true;
+            }
+        }
+    }
+
+    fn update(node: &mut SeqNode<T>) {
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+    }
+
+    fn size(node: &SeqLink<T>) -> usize {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
+    pub fn iter(&self) -> SeqIter<'_, T> {
+        let mut iter = SeqIter {
+            stack: Vec::new(),
+            len: self.len(),
+        };
+        iter.push_left(self.root.as_deref(), false);
+        iter
+    }
+
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        Self::validate_node(&self.root).map(|_| ())
+    }
+
+    fn validate_node(node: &SeqLink<T>) -> Result<usize, InvariantViolation> {
+        let node = match node {
+            Some(node) => node,
+            None => return Ok(0),
+        };
+
+        if [&node.left, &node.right].into_iter().flatten().any(|child| child.priority > node.priority) {
+            return Err(InvariantViolation::HeapOrder);
+        }
+        let size = 1 + Self::validate_node(&node.left)? + Self::validate_node(&node.right)?;
+        if node.size != size {
+            return Err(InvariantViolation::SizeMismatch);
+        }
+        Ok(size)
+    }
+}
+
+impl<T> SeqNode<T> {
+    // The children in sequence order, given whether an odd number of
+    // reversals are pending on this node and its ancestors.
+    fn children(&self, flipped: bool) -> (Option<&SeqNode<T>>, Option<&SeqNode<T>>) {
+        if flipped {
+            (self.right.as_deref(), self.left.as_deref())
+        } else {
+            (self.left.as_deref(), self.right.as_deref())
+        }
+    }
+}
+
+impl<T> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for ImplicitTreap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut sequence = ImplicitTreap::new();
+        for value in iter {
+            sequence.push(value);
+        }
+        sequence
+    }
+}
+
+pub struct SeqIter<'a, T> {
+    // Each node is paired with whether its children are reversed.
+    stack: Vec<(&'a SeqNode<T>, bool)>,
+    len: usize,
+}
+
+impl<'a, T> SeqIter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a SeqNode<T>>, mut flipped: bool) {
+        while let Some(current) = node {
+            flipped ^= current.reversed;
+            self.stack.push((current, flipped));
+            node = current.children(flipped).0;
+        }
+    }
+}
+
+impl<'a, T> Iterator for SeqIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let (node, flipped) = self.stack.pop()?;
+        self.push_left(node.children(flipped).1, flipped);
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for SeqIter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a ImplicitTreap<T> {
+    type Item = &'a T;
+    type IntoIter = SeqIter<'a, T>;
+
+    fn into_iter(self) -> SeqIter<'a, T> {
+        self.iter()
+    }
+}
+
+// Xorshift64 priority source; treaps only need priorities that are
+// independent of the keys, not cryptographic randomness.
+#[derive(Debug, Clone)]
+struct XorShift(u64);
+
+impl XorShift {
+    fn new() -> Self {
+        let seed = RandomState::new().build_hasher().finish();
+        XorShift(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn fork(&mut self) -> Self {
+        XorShift(self.next() | 1)
+    }
+}
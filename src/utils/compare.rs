@@ -0,0 +1,46 @@
+use std::cmp::Ordering;
+
+// An ordering on `T` supplied at construction time, so trees and heaps can
+// order values by a derived key or in reverse without newtype wrappers.
+//
+// Any `Fn(&T, &T) -> Ordering` closure is a comparator, and so is a boxed
+// `dyn Compare<T>` when the comparator has to be chosen at run time.
+//
+// The ordered maps and `IntervalTree` do not take a comparator; they always
+// order keys by `Ord`.
+pub trait Compare<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+// The comparator used by default, which defers to `Ord`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Natural;
+
+impl<T: Ord + ?Sized> Compare<T> for Natural {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+// Reverses another comparator. The heaps pop the value that comes first
+// under their comparator, so `Reversed` turns a min-heap into a max-heap.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reversed<C = Natural>(pub C);
+
+impl<T: ?Sized, C: Compare<T>> Compare<T> for Reversed<C> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.0.compare(b, a)
+    }
+}
+
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Compare<T> for F {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
+    }
+}
+
+impl<T: ?Sized> Compare<T> for Box<dyn Compare<T> + '_> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (**self).compare(a, b)
+    }
+}
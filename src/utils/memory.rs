@@ -1,14 +1,207 @@
+use std::ops::{Index, IndexMut};
+use std::vec;
+
+// Slot storage with stable ids. Removing an item frees its slot for reuse,
+// and every reuse bumps the slot's generation so that ids of removed items
+// never resolve to whatever is stored there later.
+#[derive(Debug, Clone)]
 pub struct Arena<T> {
-    items: Vec<T>,
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: usize,
+    item: Option<T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Id {
+    index: usize,
+    generation: usize,
+}
+
+// Translates ids into an arena that has been appended to another one.
+#[derive(Debug, Clone, Copy)]
+pub struct Relocation {
+    offset: usize,
+}
+
+impl Relocation {
+    pub fn apply(self, id: Id) -> Id {
+        Id {
+            index: id.index + self.offset,
+            generation: id.generation,
+        }
+    }
 }
 
 impl<T> Arena<T> {
     pub fn new() -> Self {
-        Arena { items: Vec::new() }
+        Arena {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
     }
 
     pub fn alloc(&mut self, item: T) -> &mut T {
-        self.items.push(item);
-        self.items.last_mut().unwrap()
+        let id = self.insert(item);
+        &mut self[id]
+    }
+
+    pub fn insert(&mut self, item: T) -> Id {
+        self.insert_with(|_| item)
+    }
+
+    // Like `insert`, but builds the item from its own id, for items that
+    // need to refer to themselves.
+    pub fn insert_with<F: FnOnce(Id) -> T>(&mut self, f: F) -> Id {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    item: None,
+                });
+                self.slots.len() - 1
+            }
+        };
+
+        let slot = &mut self.slots[index];
+        let id = Id {
+            index,
+            generation: slot.generation,
+        };
+        slot.item = Some(f(id));
+        self.len += 1;
+        id
+    }
+
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        let slot = self.slots.get_mut(id.index)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+
+        let item = slot.item.take()?;
+        slot.generation += 1;
+        self.free.push(id.index);
+        self.len -= 1;
+        Some(item)
+    }
+
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.slots
+            .get(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.item.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        self.slots
+            .get_mut(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.item.as_mut())
+    }
+
+    // Moves every item of `other` into this arena, after its own slots, in
+    // O(m). `relocate` is called on each moved item so it can translate the
+    // ids it holds, and the returned `Relocation` translates any other ids
+    // into `other`. Ids into this arena are unaffected.
+    pub fn append<F: FnMut(&mut T, Relocation)>(&mut self, other: Arena<T>, mut relocate: F) -> Relocation {
+        let relocation = Relocation {
+            offset: self.slots.len(),
+        };
+        self.free.extend(other.free.into_iter().map(|index| index + relocation.offset));
+        self.len += other.len;
+        for mut slot in other.slots {
+            if let Some(item) = &mut slot.item {
+                relocate(item, relocation);
+            }
+            self.slots.push(slot);
+        }
+        relocation
+    }
+
+    // Removes every item. The slots are kept so that ids of the removed
+    // items never resolve to items inserted later.
+    pub fn clear(&mut self) {
+        self.free.clear();
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.item.take().is_some() {
+                slot.generation += 1;
+            }
+            self.free.push(index);
+        }
+        self.len = 0;
+    }
+
+    pub fn contains(&self, id: Id) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<T> Index<Id> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, id: Id) -> &T {
+        self.get(id).expect("id refers to a removed item")
+    }
+}
+
+impl<T> IndexMut<Id> for Arena<T> {
+    fn index_mut(&mut self, id: Id) -> &mut T {
+        self.get_mut(id).expect("id refers to a removed item")
     }
 }
+
+impl<T> IntoIterator for Arena<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            slots: self.slots.into_iter(),
+            remaining: self.len,
+        }
+    }
+}
+
+// Yields the items left in an arena, in slot order.
+pub struct IntoIter<T> {
+    slots: vec::IntoIter<Slot<T>>,
+    remaining: usize,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let item = self.slots.by_ref().find_map(|slot| slot.item)?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
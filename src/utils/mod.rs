@@ -1,2 +1,3 @@
 pub mod memory;
 pub mod benchmarking;
+pub mod compare;
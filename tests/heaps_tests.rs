@@ -1,8 +1,11 @@
 #[cfg(test)]
 mod tests {
     use super::super::heaps::binary_heap::BinaryHeap;
+    use super::super::heaps::indexed_binary_heap::IndexedBinaryHeap;
     use super::super::heaps::fibonacci_heap::FibonacciHeap;
     use super::super::heaps::binomial_heap::BinomialHeap;
+    use super::super::heaps::priority_queue::PriorityQueue;
+    use super::super::utils::compare::{Natural, Reversed};
 
     #[test]
     fn test_binary_heap() {
@@ -39,4 +42,193 @@ mod tests {
         assert_eq!(heap.pop(), Some(20));
         assert_eq!(heap.pop(), None);
     }
+
+    #[test]
+    fn test_heap_comparator() {
+        let mut max_heap = BinaryHeap::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        let mut pair_heap = FibonacciHeap::with_comparator(|a: &(i32, char), b: &(i32, char)| b.0.cmp(&a.0));
+        for value in [10, 20, 5] {
+            max_heap.push(value);
+            pair_heap.push((value, 'x'));
+        }
+        assert_eq!(max_heap.pop(), Some(20));
+        assert_eq!(max_heap.pop(), Some(10));
+        assert_eq!(pair_heap.pop(), Some((20, 'x')));
+        assert_eq!(pair_heap.pop(), Some((10, 'x')));
+    }
+
+    #[test]
+    fn test_binomial_heap_comparator() {
+        let descending = |a: &i32, b: &i32| b.cmp(a);
+        let mut heap = BinomialHeap::with_comparator(descending);
+        let mut other = BinomialHeap::with_comparator(descending);
+        for value in [10, 20, 5] {
+            heap.push(value);
+        }
+        for value in [15, 25, 1, 30] {
+            other.push(value);
+        }
+        assert_eq!(heap.pop(), Some(20));
+        heap.merge(other);
+        assert_eq!(heap.peek(), Some(&30));
+        let values: Vec<_> = std::iter::from_fn(|| heap.pop()).collect();
+        assert_eq!(values, vec![30, 25, 15, 10, 5, 1]);
+    }
+
+    #[test]
+    fn test_fibonacci_decrease_key() {
+        let mut heap = FibonacciHeap::new();
+        let handles: Vec<_> = (0..20).map(|i| heap.push(100 + i)).collect();
+        assert_eq!(heap.pop(), Some(100));
+
+        heap.decrease_key(handles[15], 50);
+        heap.decrease_key(handles[7], 60);
+        heap.decrease_key(handles[15], 40);
+        assert_eq!(heap.delete(handles[7]), 60);
+        assert_eq!(heap.delete(handles[19]), 119);
+        assert_eq!(heap.len(), 17);
+
+        assert_eq!(heap.pop(), Some(40));
+        let mut rest = Vec::new();
+        while let Some(value) = heap.pop() {
+            rest.push(value);
+        }
+        let expected: Vec<_> = (101..119).filter(|&i| i != 107 && i != 115).collect();
+        assert_eq!(rest, expected);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_fibonacci_heap_merge() {
+        let mut first = FibonacciHeap::new();
+        let mut second = FibonacciHeap::new();
+        let kept = first.push(30);
+        first.push(10);
+        let moved: Vec<_> = [40, 20, 50].into_iter().map(|value| second.push(value)).collect();
+        assert_eq!(second.pop(), Some(20));
+
+        let handles = first.merge(second);
+        assert_eq!(first.len(), 4);
+        assert_eq!(first.peek(), Some(&10));
+        assert_eq!(first.get(handles.get(moved[2])), Some(&50));
+        assert_eq!(first.get(handles.get(moved[1])), None);
+
+        first.decrease_key(handles.get(moved[2]), 5);
+        first.decrease_key(kept, 7);
+        let values: Vec<_> = std::iter::from_fn(|| first.pop()).collect();
+        assert_eq!(values, vec![5, 7, 10, 40]);
+
+        let stale = first.push(1);
+        first.clear();
+        first.push(2);
+        assert!(first.get(stale).is_none());
+        assert_eq!(first.len(), 1);
+    }
+
+    #[test]
+    fn test_binomial_heap_merge() {
+        let mut evens = BinomialHeap::new();
+        let mut odds = BinomialHeap::new();
+        for i in 0..50 {
+            evens.push(i * 2);
+            odds.push(99 - i * 2);
+        }
+        evens.merge(odds);
+        assert_eq!(evens.len(), 100);
+        assert_eq!(evens.peek(), Some(&0));
+
+        let mut popped = Vec::new();
+        while let Some(value) = evens.pop() {
+            popped.push(value);
+        }
+        assert_eq!(popped, (0..100).collect::<Vec<_>>());
+        assert!(evens.is_empty());
+    }
+
+    fn schedule<Q: PriorityQueue<Item = u32>>(mut first: Q, mut second: Q) -> (Vec<u32>, Vec<u32>) {
+        for task in [7, 3, 9] {
+            first.push(task);
+        }
+        for task in [4, 8, 1, 6] {
+            second.push(task);
+        }
+        first.merge(second);
+        assert_eq!(first.len(), 7);
+
+        let head: Vec<u32> = first.drain().take(2).collect();
+        assert!(first.is_empty());
+        first.push(5);
+        first.push(2);
+        (head, first.into_sorted_vec())
+    }
+
+    #[test]
+    fn test_priority_queue() {
+        assert_eq!(
+            schedule(BinaryHeap::new(), BinaryHeap::new()),
+            (vec![9, 8], vec![5, 2])
+        );
+        assert_eq!(
+            schedule(BinomialHeap::new(), BinomialHeap::new()),
+            (vec![1, 3], vec![2, 5])
+        );
+        assert_eq!(
+            schedule(FibonacciHeap::new(), FibonacciHeap::new()),
+            (vec![1, 3], vec![2, 5])
+        );
+    }
+
+    #[test]
+    fn test_priority_queue_comparator() {
+        assert_eq!(
+            schedule(BinaryHeap::with_comparator(Natural), BinaryHeap::with_comparator(Natural)),
+            (vec![1, 3], vec![2, 5])
+        );
+        assert_eq!(
+            schedule(
+                BinomialHeap::with_comparator(Reversed(Natural)),
+                BinomialHeap::with_comparator(Reversed(Natural))
+            ),
+            (vec![9, 8], vec![5, 2])
+        );
+        assert_eq!(
+            schedule(
+                FibonacciHeap::with_comparator(Reversed(Natural)),
+                FibonacciHeap::with_comparator(Reversed(Natural))
+            ),
+            (vec![9, 8], vec![5, 2])
+        );
+
+        let mut heap = IndexedBinaryHeap::with_comparator(Natural);
+        for (task, priority) in [("build", 3), ("test", 5), ("lint", 1)] {
+            heap.push(task, priority);
+        }
+        let order: Vec<_> = heap.drain().map(|(task, _)| task).collect();
+        assert_eq!(order, vec!["lint", "build", "test"]);
+    }
+
+    #[test]
+    fn test_indexed_binary_heap() {
+        let mut heap = IndexedBinaryHeap::new();
+        for (task, priority) in [("build", 3), ("test", 5), ("lint", 1), ("deploy", 2)] {
+            assert_eq!(heap.push(task, priority), None);
+        }
+        assert_eq!(heap.peek(), Some((&"test", &5)));
+        assert!(heap.contains(&"lint"));
+
+        assert_eq!(heap.change_priority(&"lint", 8), Some(1));
+        assert_eq!(heap.change_priority(&"test", 0), Some(5));
+        assert_eq!(heap.change_priority(&"docs", 4), None);
+        assert_eq!(heap.push("deploy", 6), Some(2));
+        assert_eq!(heap.remove(&"build"), Some(3));
+        assert_eq!(heap.remove(&"build"), None);
+        assert!(!heap.contains(&"build"));
+        assert_eq!(heap.get(&"deploy"), Some(&6));
+        assert_eq!(heap.len(), 3);
+
+        assert_eq!(heap.pop(), Some(("lint", 8)));
+        assert_eq!(heap.pop(), Some(("deploy", 6)));
+        assert_eq!(heap.pop(), Some(("test", 0)));
+        assert_eq!(heap.pop(), None);
+    }
 }
@@ -1,9 +1,22 @@
 #[cfg(test)]
 mod tests {
     use super::super::trees::binary_search_tree::BinarySearchTree;
+    use super::super::trees::splay_tree::SplayTree;
+    use super::super::trees::treap::{ImplicitTreap, Treap};
     use super::super::trees::avl_tree::AVLTree;
     use super::super::trees::red_black_tree::RedBlackTree;
+    use super::super::trees::persistent_avl_tree::PersistentAVLTree;
+    use super::super::trees::persistent_red_black_tree::PersistentRedBlackTree;
     use super::super::trees::b_tree::BTree;
+    use super::super::trees::b_tree::persistent::PersistentBTree;
+    use super::super::trees::b_plus_tree::BPlusTree;
+    use super::super::trees::interval_tree::IntervalTree;
+    use super::super::trees::avl_map::AVLMap;
+    use super::super::trees::red_black_map::RedBlackMap;
+    use super::super::trees::b_tree_map::BTreeOrderedMap;
+    use super::super::trees::entry::Entry;
+    use super::super::trees::summary::Summary;
+    use super::super::utils::compare::Compare;
 
     #[test]
     fn test_binary_search_tree_insert() {
@@ -29,6 +42,44 @@ mod tests {
         assert!(!avl.contains(&15));
     }
 
+    #[test]
+    fn test_avl_tree_remove() {
+        let mut avl = AVLTree::new();
+        for i in 0..100 {
+            avl.insert(i);
+        }
+        for i in (0..100).step_by(2) {
+            assert_eq!(avl.remove(&i), Some(i));
+        }
+        assert_eq!(avl.remove(&0), None);
+        assert!(!avl.contains(&10));
+        assert!(avl.contains(&11));
+        assert_eq!(avl.in_order_traversal().len(), 50);
+        assert_eq!(avl.pop_min(), Some(1));
+        assert_eq!(avl.pop_max(), Some(99));
+        assert_eq!(avl.in_order_traversal().first(), Some(&&3));
+    }
+
+    #[test]
+    fn test_avl_tree_rank_select() {
+        let mut avl = AVLTree::new();
+        for i in (0..100).rev() {
+            avl.insert(i * 2);
+        }
+        assert_eq!(avl.len(), 100);
+        assert_eq!(avl.rank(&0), 0);
+        assert_eq!(avl.rank(&51), 26);
+        assert_eq!(avl.rank(&1000), 100);
+        assert_eq!(avl.select(0), Some(&0));
+        assert_eq!(avl.select(25), Some(&50));
+        assert_eq!(avl.select(100), None);
+        avl.remove(&50);
+        avl.pop_min();
+        assert_eq!(avl.len(), 98);
+        assert_eq!(avl.select(24), Some(&52));
+        assert_eq!(avl.rank(&52), 24);
+    }
+
     #[test]
     fn test_red_black_tree_insert() {
         let mut rbt = RedBlackTree::new();
@@ -41,6 +92,24 @@ mod tests {
         assert!(!rbt.contains(&15));
     }
 
+    #[test]
+    fn test_red_black_tree_remove() {
+        let mut rbt = RedBlackTree::new();
+        for i in 0..100 {
+            rbt.insert(i);
+        }
+        for i in (0..100).step_by(3) {
+            assert_eq!(rbt.remove(&i), Some(i));
+        }
+        assert_eq!(rbt.remove(&3), None);
+        assert!(!rbt.contains(&30));
+        assert!(rbt.contains(&31));
+        assert_eq!(rbt.in_order_traversal().len(), 66);
+        assert_eq!(rbt.delete_min(), Some(1));
+        assert_eq!(rbt.delete_max(), Some(98));
+        assert_eq!(rbt.in_order_traversal().first(), Some(&&2));
+    }
+
     #[test]
     fn test_b_tree_insert() {
         let mut btree = BTree::new(2);
@@ -52,4 +121,815 @@ mod tests {
         assert!(btree.contains(&5));
         assert!(!btree.contains(&15));
     }
+
+    #[test]
+    fn test_b_tree_remove() {
+        let mut btree = BTree::new(2);
+        for i in 0..100 {
+            btree.insert(i);
+        }
+        for i in (0..100).step_by(2) {
+            assert!(btree.remove(&i));
+        }
+        assert!(!btree.remove(&0));
+        assert!(!btree.contains(&10));
+        assert!(btree.contains(&11));
+        assert_eq!(btree.in_order_traversal().len(), 50);
+        for i in (1..100).step_by(2) {
+            assert!(btree.remove(&i));
+        }
+        assert!(btree.in_order_traversal().is_empty());
+        assert!(!btree.contains(&11));
+    }
+
+    #[test]
+    fn test_avl_map() {
+        let mut map = AVLMap::new();
+        assert_eq!(map.insert(1, "one"), None);
+        assert_eq!(map.insert(2, "two"), None);
+        assert_eq!(map.insert(1, "uno"), Some("one"));
+        assert_eq!(map.get(&1), Some(&"uno"));
+        *map.get_mut(&2).unwrap() = "dos";
+        assert_eq!(map.get(&2), Some(&"dos"));
+        assert_eq!(map.remove(&1), Some("uno"));
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_red_black_map() {
+        let mut map = RedBlackMap::new();
+        for i in 0..50 {
+            assert_eq!(map.insert(i, i * 10), None);
+        }
+        assert_eq!(map.insert(7, 0), Some(70));
+        assert_eq!(map.remove(&8), Some(80));
+        assert_eq!(map.remove(&8), None);
+        assert_eq!(map.get(&7), Some(&0));
+        assert_eq!(map.len(), 49);
+    }
+
+    #[test]
+    fn test_b_tree_map() {
+        let mut map = BTreeOrderedMap::new(2);
+        for i in 0..50 {
+            assert_eq!(map.insert(i, i * 10), None);
+        }
+        assert_eq!(map.insert(7, 0), Some(70));
+        for i in 10..40 {
+            assert_eq!(map.remove(&i), Some(i * 10));
+        }
+        assert_eq!(map.get(&7), Some(&0));
+        assert_eq!(map.get(&20), None);
+        assert_eq!(map.len(), 20);
+    }
+
+    #[test]
+    fn test_map_entry() {
+        let mut map = AVLMap::new();
+        for word in ["a", "b", "a", "c", "a"] {
+            *map.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(map.get(&"a"), Some(&3));
+        map.entry("b").and_modify(|count| *count += 10).or_insert(0);
+        assert_eq!(map.get(&"b"), Some(&11));
+        match map.entry("d") {
+            Entry::Vacant(entry) => {
+                entry.insert(4);
+            }
+            Entry::Occupied(_) => panic!("unexpected occupied entry"),
+        }
+        assert_eq!(map.get(&"d"), Some(&4));
+    }
+
+    #[test]
+    fn test_avl_tree_range() {
+        let mut avl = AVLTree::new();
+        for i in (0..50).step_by(5) {
+            avl.insert(i);
+        }
+        assert_eq!(avl.range(10..25).collect::<Vec<_>>(), vec![&10, &15, &20]);
+        assert_eq!(avl.range(..=5).collect::<Vec<_>>(), vec![&0, &5]);
+        assert_eq!(avl.floor(&12), Some(&10));
+        assert_eq!(avl.ceiling(&12), Some(&15));
+        assert_eq!(avl.predecessor(&10), Some(&5));
+        assert_eq!(avl.successor(&45), None);
+    }
+
+    #[test]
+    fn test_red_black_tree_range() {
+        let mut rbt = RedBlackTree::new();
+        for i in (0..50).step_by(5) {
+            rbt.insert(i);
+        }
+        assert_eq!(rbt.range(12..=30).collect::<Vec<_>>(), vec![&15, &20, &25, &30]);
+        assert_eq!(rbt.range(40..).collect::<Vec<_>>(), vec![&40, &45]);
+        assert_eq!(rbt.floor(&-1), None);
+        assert_eq!(rbt.ceiling(&20), Some(&20));
+        assert_eq!(rbt.predecessor(&21), Some(&20));
+        assert_eq!(rbt.successor(&20), Some(&25));
+    }
+
+    #[test]
+    fn test_b_tree_range() {
+        let mut btree = BTree::new(2);
+        for i in (0..50).step_by(5) {
+            btree.insert(i);
+        }
+        assert_eq!(btree.range(10..25).collect::<Vec<_>>(), vec![&10, &15, &20]);
+        assert_eq!(btree.range(..).count(), 10);
+        assert_eq!(btree.floor(&49), Some(&45));
+        assert_eq!(btree.ceiling(&46), None);
+        assert_eq!(btree.predecessor(&0), None);
+        assert_eq!(btree.successor(&12), Some(&15));
+    }
+
+    #[test]
+    fn test_binary_search_tree_iterators() {
+        let mut bst = BinarySearchTree::new();
+        for i in [4, 2, 6, 1, 3, 5, 7] {
+            bst.insert(i);
+        }
+        assert_eq!(bst.iter().len(), 7);
+        assert_eq!(bst.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4, &5, &6, &7]);
+        assert_eq!(bst.iter().rev().collect::<Vec<_>>(), vec![&7, &6, &5, &4, &3, &2, &1]);
+        assert_eq!(bst.pre_order().collect::<Vec<_>>(), vec![&4, &2, &1, &3, &6, &5, &7]);
+        assert_eq!(bst.post_order().collect::<Vec<_>>(), vec![&1, &3, &2, &5, &7, &6, &4]);
+        assert_eq!(bst.level_order().collect::<Vec<_>>(), vec![&4, &2, &6, &1, &3, &5, &7]);
+        assert_eq!(bst.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_binary_search_tree_degenerate_iter() {
+        // Sorted input makes a single path deep enough to overflow the stack
+        // if insert, contains, iteration or drop recursed. Building the path
+        // is quadratic, so this stays well past the depth where the recursive
+        // versions overflowed rather than going larger.
+        let mut bst = BinarySearchTree::new();
+        for i in 0..50_000 {
+            bst.insert(i);
+        }
+        assert!(bst.contains(&49_999));
+        assert!(!bst.contains(&50_000));
+        assert_eq!(bst.iter().next_back(), Some(&49_999));
+        assert_eq!(bst.iter().map(|&i| i as u64).sum::<u64>(), (0..50_000).sum::<u64>());
+
+        let copy = bst.clone();
+        assert_eq!(copy.len(), 50_000);
+        assert!(copy.validate().is_ok());
+        let mut values = copy.into_iter();
+        assert_eq!(values.next(), Some(0));
+        assert_eq!(values.next_back(), Some(49_999));
+        assert_eq!(values.len(), 49_998);
+        drop(values);
+        drop(bst);
+    }
+
+    #[test]
+    fn test_balanced_tree_iterators() {
+        let mut avl = AVLTree::new();
+        let mut rbt = RedBlackTree::new();
+        for i in 0..20 {
+            avl.insert(i);
+            rbt.insert(i);
+        }
+        let mut iter = avl.iter();
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&19));
+        assert_eq!(iter.len(), 18);
+        assert_eq!(rbt.iter().rev().take(2).collect::<Vec<_>>(), vec![&19, &18]);
+        assert_eq!(avl.level_order().count(), 20);
+        let mut values = avl.into_iter();
+        assert_eq!(values.next_back(), Some(19));
+        assert_eq!(values.next(), Some(0));
+        assert_eq!(values.len(), 18);
+        assert_eq!(values.rev().take(3).collect::<Vec<_>>(), vec![18, 17, 16]);
+        assert_eq!(rbt.into_iter().sum::<i32>(), (0..20).sum::<i32>());
+    }
+
+    #[test]
+    fn test_b_tree_iterators() {
+        let mut btree = BTree::new(2);
+        for i in (0..30).rev() {
+            btree.insert(i);
+        }
+        assert_eq!(btree.iter().len(), 30);
+        assert_eq!(btree.iter().copied().collect::<Vec<_>>(), (0..30).collect::<Vec<_>>());
+        assert_eq!(btree.iter().rev().copied().collect::<Vec<_>>(), (0..30).rev().collect::<Vec<_>>());
+        assert_eq!(btree.pre_order().count(), 30);
+        assert_eq!(btree.post_order().count(), 30);
+        assert_eq!(btree.level_order().count(), 30);
+        let mut values = btree.clone().into_iter();
+        assert_eq!(values.next(), Some(0));
+        assert_eq!(values.next_back(), Some(29));
+        assert_eq!(values.len(), 28);
+        assert_eq!(values.rev().collect::<Vec<_>>(), (1..29).rev().collect::<Vec<_>>());
+        assert_eq!(btree.into_iter().collect::<Vec<_>>(), (0..30).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_avl_tree_set_operations() {
+        let build = |values: &[i32]| {
+            let mut avl = AVLTree::new();
+            for &value in values {
+                avl.insert(value);
+            }
+            avl
+        };
+        let (a, b) = (build(&[1, 2, 3, 4, 5]), build(&[4, 5, 6, 7]));
+        assert_eq!(a.clone().union(b.clone()).in_order_traversal(), vec![&1, &2, &3, &4, &5, &6, &7]);
+        assert_eq!(a.clone().intersection(b.clone()).in_order_traversal(), vec![&4, &5]);
+        assert_eq!(a.clone().difference(b.clone()).in_order_traversal(), vec![&1, &2, &3]);
+        assert_eq!(a.symmetric_difference(b).in_order_traversal(), vec![&1, &2, &3, &6, &7]);
+    }
+
+    #[test]
+    fn test_red_black_tree_set_operations() {
+        let build = |values: std::ops::Range<i32>| {
+            let mut rbt = RedBlackTree::new();
+            for value in values {
+                rbt.insert(value);
+            }
+            rbt
+        };
+        let (a, b) = (build(0..60), build(40..100));
+        assert_eq!(a.clone().union(b.clone()).len(), 100);
+        let common = a.clone().intersection(b.clone());
+        assert_eq!(common.len(), 20);
+        assert_eq!(common.iter().next(), Some(&40));
+        assert_eq!(a.clone().difference(b.clone()).iter().next_back(), Some(&39));
+        assert_eq!(a.symmetric_difference(b).len(), 80);
+    }
+
+    #[test]
+    fn test_b_tree_set_operations() {
+        let build = |values: &[i32]| {
+            let mut btree = BTree::new(2);
+            for &value in values {
+                btree.insert(value);
+            }
+            btree
+        };
+        let (a, b) = (build(&[1, 3, 5, 7, 9]), build(&[3, 6, 9]));
+        assert_eq!(a.clone().union(b.clone()).in_order_traversal(), vec![&1, &3, &5, &6, &7, &9]);
+        assert_eq!(a.clone().intersection(b.clone()).in_order_traversal(), vec![&3, &9]);
+        assert_eq!(a.clone().difference(b.clone()).in_order_traversal(), vec![&1, &5, &7]);
+        assert_eq!(a.symmetric_difference(b).in_order_traversal(), vec![&1, &5, &6, &7]);
+    }
+
+    #[test]
+    fn test_b_tree_set_operations_unbalanced() {
+        let large = BTree::from_sorted_iter(3, (0..100_000).map(|i| i * 2), 1.0);
+        let mut small = BTree::new(3);
+        for value in [-7, 0, 3, 5_000, 77_777, 150_000, 199_998, 250_001] {
+            small.insert(value);
+        }
+
+        let union = large.clone().union(small.clone());
+        assert_eq!(union.validate(), Ok(()));
+        assert_eq!(union.len(), 100_004);
+        assert!([-7, 3, 77_777, 250_001].iter().all(|value| union.contains(value)));
+
+        let common = large.clone().intersection(small.clone());
+        assert_eq!(common.in_order_traversal(), vec![&0, &5_000, &150_000, &199_998]);
+        let common = small.clone().intersection(large.clone());
+        assert_eq!(common.validate(), Ok(()));
+        assert_eq!(common.len(), 4);
+
+        let difference = large.clone().difference(small.clone());
+        assert_eq!(difference.validate(), Ok(()));
+        assert_eq!(difference.len(), 99_996);
+        assert!(!difference.contains(&150_000) && difference.contains(&150_002));
+        let difference = small.clone().difference(large.clone());
+        assert_eq!(difference.in_order_traversal(), vec![&-7, &3, &77_777, &250_001]);
+
+        // Trees with different minimum degrees are combined too.
+        let mut narrow = BTree::new(2);
+        narrow.insert(1);
+        narrow.insert(4);
+        let symmetric = large.symmetric_difference(narrow);
+        assert_eq!(symmetric.validate(), Ok(()));
+        assert_eq!(symmetric.len(), 100_000);
+        assert!(symmetric.contains(&1) && !symmetric.contains(&4));
+    }
+
+    #[test]
+    fn test_avl_tree_split_off_append() {
+        let mut avl = AVLTree::new();
+        for i in 0..100 {
+            avl.insert(i);
+        }
+        let mut upper = avl.split_off(&60);
+        assert_eq!(avl.len(), 60);
+        assert_eq!(upper.len(), 40);
+        assert_eq!(avl.iter().next_back(), Some(&59));
+        assert_eq!(upper.iter().next(), Some(&60));
+        avl.append(&mut upper);
+        assert!(upper.is_empty());
+        assert_eq!(avl.len(), 100);
+        assert_eq!(avl.select(60), Some(&60));
+    }
+
+    #[test]
+    fn test_red_black_tree_split_off_append() {
+        let mut rbt = RedBlackTree::new();
+        for i in 0..100 {
+            rbt.insert(i);
+        }
+        let mut upper = rbt.split_off(&25);
+        assert_eq!(rbt.len(), 25);
+        assert_eq!(upper.len(), 75);
+        assert!(!rbt.contains(&25));
+        assert!(upper.contains(&25));
+        upper.append(&mut rbt);
+        assert!(rbt.is_empty());
+        assert_eq!(upper.iter().copied().collect::<Vec<_>>(), (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut bst = BinarySearchTree::new();
+        let mut avl = AVLTree::new();
+        let mut rbt = RedBlackTree::new();
+        let mut btree = BTree::new(3);
+        assert_eq!(bst.validate(), Ok(()));
+        assert_eq!(avl.validate(), Ok(()));
+        assert_eq!(rbt.validate(), Ok(()));
+        assert_eq!(btree.validate(), Ok(()));
+
+        for i in 0..200 {
+            let key = (i * 37) % 200;
+            bst.insert(key);
+            avl.insert(key);
+            rbt.insert(key);
+            btree.insert(key);
+        }
+        assert_eq!(bst.validate(), Ok(()));
+        assert_eq!(avl.validate(), Ok(()));
+        assert_eq!(rbt.validate(), Ok(()));
+        assert_eq!(btree.validate(), Ok(()));
+
+        for i in (0..200).step_by(3) {
+            avl.remove(&i);
+            rbt.remove(&i);
+            btree.remove(&i);
+        }
+        assert_eq!(avl.validate(), Ok(()));
+        assert_eq!(rbt.validate(), Ok(()));
+        assert_eq!(btree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_b_plus_tree() {
+        let mut tree = BPlusTree::new(2);
+        for i in 0..100 {
+            assert_eq!(tree.insert(i, i * 10), None);
+        }
+        assert_eq!(tree.insert(5, 0), Some(50));
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.get(&5), Some(&0));
+        *tree.get_mut(&6).unwrap() += 1;
+        assert_eq!(tree.get(&6), Some(&61));
+        assert_eq!(tree.validate(), Ok(()));
+
+        let keys: Vec<i32> = tree.range(20..25).map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec![20, 21, 22, 23, 24]);
+        assert_eq!(tree.range(95..).count(), 5);
+
+        for i in (0..100).step_by(2) {
+            assert_eq!(tree.remove(&i), Some(if i == 6 { 61 } else if i == 5 { 0 } else { i * 10 }));
+        }
+        assert_eq!(tree.remove(&0), None);
+        assert!(!tree.contains_key(&10));
+        assert!(tree.contains_key(&11));
+        assert_eq!(tree.len(), 50);
+        assert_eq!(tree.validate(), Ok(()));
+        let keys: Vec<i32> = tree.range(10..=16).map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec![11, 13, 15]);
+        assert_eq!(tree.iter().len(), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "minimum degree")]
+    fn test_b_plus_tree_degree_one() {
+        BPlusTree::<i32, i32>::new(1);
+    }
+
+    #[test]
+    fn test_persistent_b_tree() {
+        let path = std::env::temp_dir().join(format!("persistent_b_tree_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut tree = PersistentBTree::open(&path, 3).unwrap();
+        for i in 0..1000u32 {
+            tree.insert((i * 7919) % 1000).unwrap();
+        }
+        tree.insert(5).unwrap();
+        assert_eq!(tree.len(), 1000);
+        tree.validate().unwrap();
+        for i in (0..1000).step_by(2) {
+            assert!(tree.remove(&i).unwrap());
+        }
+        assert!(!tree.remove(&0).unwrap());
+        tree.validate().unwrap();
+        tree.close().unwrap();
+
+        assert!(PersistentBTree::<u32>::open(&path, 4).is_err());
+        let tree = PersistentBTree::open(&path, 3).unwrap();
+        assert_eq!(tree.len(), 500);
+        tree.validate().unwrap();
+        for i in 0..1000 {
+            assert_eq!(tree.contains(&i).unwrap(), i % 2 == 1);
+        }
+        let mut tree = tree;
+        for i in (1..1000).step_by(2) {
+            assert!(tree.remove(&i).unwrap());
+        }
+        assert!(tree.is_empty());
+        assert!(!tree.contains(&1).unwrap());
+        tree.validate().unwrap();
+        tree.close().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let _ = std::fs::remove_file(path.with_extension("db-wal"));
+    }
+
+    #[test]
+    fn test_persistent_b_tree_crash_recovery() {
+        let dir = std::env::temp_dir();
+        let name = |suffix: &str| dir.join(format!("crash_b_tree_{}{}", std::process::id(), suffix));
+        let (path, wal) = (name(".db"), name(".db-wal"));
+        let (crash_path, crash_wal) = (name("_crash.db"), name("_crash.db-wal"));
+
+        // Every fourth operation removes a key inserted shortly before.
+        let op = |i: u32| if i % 4 == 3 { (false, (i - 2) * 37 % 50) } else { (true, i * 37 % 50) };
+
+        let mut tree = PersistentBTree::open(&path, 2).unwrap();
+        tree.checkpoint().unwrap();
+        let mut log_lens = vec![0];
+        for i in 0..60 {
+            match op(i) {
+                (true, key) => tree.insert(key).unwrap(),
+                (false, key) => assert!(tree.remove(&key).unwrap()),
+            }
+            log_lens.push(std::fs::metadata(&wal).unwrap().len() as usize);
+        }
+        // Simulate a crash: skip the checkpoint that `close` and drop perform.
+        std::mem::forget(tree);
+        let base = std::fs::read(&path).unwrap();
+        let log = std::fs::read(&wal).unwrap();
+
+        let boundaries = log_lens.iter().flat_map(|&len| [len.saturating_sub(1), len]);
+        for cut in (0..log.len()).step_by(2039).chain(boundaries).filter(|&cut| cut <= log.len()) {
+            std::fs::write(&crash_path, &base).unwrap();
+            std::fs::write(&crash_wal, &log[..cut]).unwrap();
+
+            let committed = log_lens.iter().rposition(|&len| len <= cut).unwrap() as u32;
+            let mut expected = std::collections::BTreeSet::new();
+            for i in 0..committed {
+                match op(i) {
+                    (true, key) => expected.insert(key),
+                    (false, key) => expected.remove(&key),
+                };
+            }
+
+            let tree = PersistentBTree::<u32>::open(&crash_path, 2).unwrap();
+            assert_eq!(tree.len(), expected.len(), "log cut at {}", cut);
+            tree.validate().unwrap();
+            for key in 0..50 {
+                assert_eq!(tree.contains(&key).unwrap(), expected.contains(&key), "log cut at {}", cut);
+            }
+            tree.close().unwrap();
+        }
+
+        for file in [&path, &wal, &crash_path, &crash_wal] {
+            let _ = std::fs::remove_file(file);
+        }
+    }
+
+    #[test]
+    fn test_bulk_load() {
+        let values: Vec<i32> = (0..1000).collect();
+
+        let avl = AVLTree::from_sorted(values.clone());
+        assert_eq!(avl.validate(), Ok(()));
+        assert_eq!(avl.len(), 1000);
+        assert_eq!(avl.iter().copied().collect::<Vec<_>>(), values);
+
+        let mut rbt = RedBlackTree::from_sorted(values.clone());
+        assert_eq!(rbt.validate(), Ok(()));
+        assert_eq!(rbt.iter().copied().collect::<Vec<_>>(), values);
+        rbt.insert(1000);
+        rbt.remove(&0);
+        assert_eq!(rbt.validate(), Ok(()));
+
+        for fill_factor in [0.1, 0.5, 1.0] {
+            let mut btree = BTree::from_sorted_iter(3, values.clone(), fill_factor);
+            assert_eq!(btree.validate(), Ok(()));
+            assert_eq!(btree.len(), 1000);
+            assert_eq!(btree.iter().copied().collect::<Vec<_>>(), values);
+            assert!(btree.remove(&500));
+            btree.insert(1000);
+            assert_eq!(btree.validate(), Ok(()));
+        }
+
+        let duplicates = BTree::from_sorted_iter(2, vec![1, 1, 2, 3, 3], 1.0);
+        assert_eq!(duplicates.len(), 3);
+        assert!(AVLTree::<i32>::from_sorted(vec![]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "ascending order")]
+    fn test_bulk_load_unsorted() {
+        RedBlackTree::from_sorted(vec![1, 3, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "minimum degree")]
+    fn test_bulk_load_degree_one() {
+        BTree::from_sorted_iter(1, vec![1, 2, 3], 1.0);
+    }
+
+    #[test]
+    fn test_persistent_avl_tree() {
+        let mut versions = vec![(PersistentAVLTree::new(), std::collections::BTreeSet::new())];
+        for i in 0..200 {
+            let (tree, mut expected) = versions.last().unwrap().clone();
+            let key = (i * 37) % 100;
+            let tree = if i < 100 || key % 2 == 1 {
+                expected.insert(key);
+                tree.insert(key)
+            } else {
+                expected.remove(&key);
+                tree.remove(&key)
+            };
+            versions.push((tree, expected));
+        }
+
+        for (tree, expected) in &versions {
+            assert_eq!(tree.validate(), Ok(()));
+            assert_eq!(tree.len(), expected.len());
+            assert!(tree.iter().eq(expected.iter()));
+        }
+        assert!(versions[100].0.contains(&0));
+        assert!(!versions[200].0.contains(&0));
+
+        let base = &versions[100].0;
+        let next = base.insert(1000);
+        // Only the nodes on the insert's path are copied; the rest of the
+        // 100 nodes are shared between the two versions.
+        assert_eq!(base.clone().shared_nodes(base), base.len());
+        assert!(next.shared_nodes(base) >= base.len() - 10);
+        assert!(next.contains(&1000) && !base.contains(&1000));
+    }
+
+    #[test]
+    fn test_persistent_red_black_tree() {
+        let mut versions = vec![(PersistentRedBlackTree::new(), std::collections::BTreeSet::new())];
+        for i in 0..200 {
+            let (tree, mut expected) = versions.last().unwrap().clone();
+            let key = (i * 37) % 100;
+            let tree = if i < 100 || key % 2 == 1 {
+                expected.insert(key);
+                tree.insert(key)
+            } else {
+                expected.remove(&key);
+                tree.remove(&key)
+            };
+            versions.push((tree, expected));
+        }
+
+        for (tree, expected) in &versions {
+            assert_eq!(tree.validate(), Ok(()));
+            assert_eq!(tree.len(), expected.len());
+            assert!(tree.iter().eq(expected.iter()));
+        }
+        assert!(versions[100].0.contains(&0));
+        assert!(!versions[200].0.contains(&0));
+
+        let base = &versions[100].0;
+        let next = base.insert(1000);
+        // Only the nodes on the insert's path are copied; the rest of the
+        // 100 nodes are shared between the two versions.
+        assert_eq!(base.clone().shared_nodes(base), base.len());
+        assert!(next.shared_nodes(base) >= base.len() - 10);
+        assert!(next.contains(&1000) && !base.contains(&1000));
+    }
+
+    #[test]
+    fn test_interval_tree() {
+        let mut tree = IntervalTree::new();
+        assert_eq!(tree.insert(15..20, "a"), None);
+        assert_eq!(tree.insert(10..30, "b"), None);
+        assert_eq!(tree.insert(17..19, "c"), None);
+        assert_eq!(tree.insert(5..20, "d"), None);
+        assert_eq!(tree.insert(12..15, "e"), None);
+        assert_eq!(tree.insert(30..40, "f"), None);
+        assert_eq!(tree.insert(12..15, "g"), Some("e"));
+        assert_eq!(tree.len(), 6);
+
+        let found: Vec<_> = tree.overlapping(14..16).map(|(_, value)| *value).collect();
+        assert_eq!(found, vec!["d", "b", "g", "a"]);
+        let found: Vec<_> = tree.stabbing(30).map(|(interval, _)| interval.clone()).collect();
+        assert_eq!(found, vec![30..40]);
+        let found: Vec<_> = tree.stabbing(20).map(|(interval, _)| interval.clone()).collect();
+        assert_eq!(found, vec![10..30]);
+        assert_eq!(tree.overlapping(40..50).count(), 0);
+        assert_eq!(tree.overlapping(16..16).count(), 0);
+        tree.insert(16..16, "h");
+        assert_eq!(tree.overlapping(14..18).count(), 5);
+        assert_eq!(tree.remove(&(16..16)), Some("h"));
+
+        assert_eq!(tree.remove(&(10..30)), Some("b"));
+        assert_eq!(tree.remove(&(10..30)), None);
+        assert_eq!(tree.stabbing(25).count(), 0);
+        assert_eq!(tree.len(), 5);
+    }
+
+    #[test]
+    fn test_splay_tree() {
+        let mut tree = SplayTree::new();
+        for i in 0..10_000 {
+            tree.insert(i);
+        }
+        tree.insert(5);
+        assert_eq!(tree.len(), 10_000);
+        for i in (0..10_000).step_by(7) {
+            assert!(tree.contains(&i));
+        }
+        assert!(!tree.contains(&10_000));
+        assert_eq!(tree.validate(), Ok(()));
+
+        for i in (0..10_000).step_by(2) {
+            assert_eq!(tree.remove(&i), Some(i));
+        }
+        assert_eq!(tree.remove(&0), None);
+        assert_eq!(tree.len(), 5_000);
+
+        let mut upper = tree.split(&5_000);
+        assert_eq!(tree.len(), 2_500);
+        assert_eq!(upper.len(), 2_500);
+        assert!(tree.iter().all(|&value| value < 5_000));
+        assert!(upper.iter().all(|&value| value > 5_000));
+        assert!(!upper.contains(&4_999));
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(upper.validate(), Ok(()));
+
+        tree.join(upper);
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree.iter().copied().eq((1..10_000).step_by(2)));
+    }
+
+    #[test]
+    fn test_treap() {
+        let mut treap = Treap::new();
+        for i in 0..1_000 {
+            treap.insert((i * 37) % 1_000);
+        }
+        treap.insert(0);
+        assert_eq!(treap.len(), 1_000);
+        assert_eq!(treap.validate(), Ok(()));
+
+        for i in (0..1_000).step_by(3) {
+            assert_eq!(treap.remove(&i), Some(i));
+        }
+        assert_eq!(treap.remove(&0), None);
+        assert!(!treap.contains(&3));
+        assert!(treap.contains(&4));
+
+        let upper = treap.split(&500);
+        assert!(treap.iter().all(|&value| value < 500));
+        assert!(upper.iter().all(|&value| value >= 500));
+        assert_eq!(treap.validate(), Ok(()));
+        assert_eq!(upper.validate(), Ok(()));
+
+        treap.merge(upper);
+        assert_eq!(treap.validate(), Ok(()));
+        assert!(treap.iter().copied().eq((0..1_000).filter(|i| i % 3 != 0)));
+    }
+
+    #[test]
+    fn test_implicit_treap() {
+        let mut sequence: ImplicitTreap<char> = "hello world".chars().collect();
+        assert_eq!(sequence.len(), 11);
+        assert_eq!(sequence.get(4), Some(&'o'));
+        assert_eq!(sequence.get(11), None);
+
+        sequence.reverse(6..);
+        assert_eq!(sequence.iter().collect::<String>(), "hello dlrow");
+        sequence.reverse(..);
+        assert_eq!(sequence.iter().collect::<String>(), "world olleh");
+        assert_eq!(sequence.get(0), Some(&'w'));
+
+        assert_eq!(sequence.remove(5), Some(' '));
+        assert_eq!(sequence.remove(10), None);
+        sequence.insert(5, ',');
+        sequence.insert(0, '>');
+        assert_eq!(sequence.iter().collect::<String>(), ">world,olleh");
+
+        let mut tail = sequence.split_off(7);
+        assert_eq!(tail.iter().collect::<String>(), "olleh");
+        tail.reverse(1..=3);
+        sequence.append(&mut tail);
+        assert!(tail.is_empty());
+        assert_eq!(sequence.iter().collect::<String>(), ">world,oellh");
+        assert_eq!(sequence.validate(), Ok(()));
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct SumMax {
+        sum: i64,
+        max: Option<i64>,
+    }
+
+    impl Summary<i64> for SumMax {
+        fn empty() -> Self {
+            SumMax { sum: 0, max: None }
+        }
+
+        fn of(value: &i64) -> Self {
+            SumMax { sum: *value, max: Some(*value) }
+        }
+
+        fn combine(&self, other: &Self) -> Self {
+            SumMax {
+                sum: self.sum + other.sum,
+                max: self.max.max(other.max),
+            }
+        }
+    }
+
+    #[test]
+    fn test_fold_range() {
+        let mut avl: AVLTree<i64, SumMax> = AVLTree::with_summary();
+        let mut rb: RedBlackTree<i64, SumMax> = RedBlackTree::with_summary();
+        for i in 0..200 {
+            avl.insert((i * 37) % 200);
+            rb.insert((i * 37) % 200);
+        }
+        for i in (0..200).step_by(4) {
+            avl.remove(&i);
+            rb.remove(&i);
+        }
+
+        let expected = |range: std::ops::Range<i64>| {
+            let values: Vec<i64> = range.filter(|i| i % 4 != 0).collect();
+            SumMax {
+                sum: values.iter().sum(),
+                max: values.iter().max().copied(),
+            }
+        };
+        assert_eq!(avl.fold_range(..), expected(0..200));
+        assert_eq!(rb.fold_range(..), expected(0..200));
+        assert_eq!(avl.fold_range(10..50), expected(10..50));
+        assert_eq!(rb.fold_range(10..=49), expected(10..50));
+        assert_eq!(avl.fold_range(150..), expected(150..200));
+        assert_eq!(rb.fold_range(..7), expected(0..7));
+        assert_eq!(avl.fold_range(8..9), SumMax::empty());
+
+        let upper = rb.split_off(&100);
+        assert_eq!(rb.fold_range(..), expected(0..100));
+        assert_eq!(upper.fold_range(..), expected(100..200));
+
+        let descending = |a: &i64, b: &i64| b.cmp(a);
+        let mut avl = AVLTree::<i64, SumMax, _>::with_summary_and_comparator(descending);
+        let mut rb = RedBlackTree::<i64, SumMax, _>::with_summary_and_comparator(descending);
+        for i in (0..200).filter(|i| i % 4 != 0) {
+            avl.insert(i);
+            rb.insert(i);
+        }
+        assert_eq!(avl.iter().next(), Some(&199));
+        assert_eq!(avl.fold_range(49..=10), expected(10..50));
+        assert_eq!(rb.fold_range(..=150), expected(150..200));
+    }
+
+    #[test]
+    fn test_comparator() {
+        let mut avl = AVLTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        let mut rb = RedBlackTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        let mut btree = BTree::with_comparator(2, |a: &i32, b: &i32| b.cmp(a));
+        for value in [5, 1, 9, 3, 7, 3] {
+            avl.insert(value);
+            rb.insert(value);
+            btree.insert(value);
+        }
+        assert_eq!(avl.in_order_traversal(), vec![&9, &7, &5, &3, &1]);
+        assert_eq!(rb.in_order_traversal(), vec![&9, &7, &5, &3, &1]);
+        assert_eq!(btree.in_order_traversal(), vec![&9, &7, &5, &3, &1]);
+        assert_eq!(avl.floor(&6), Some(&7));
+        assert_eq!(rb.ceiling(&6), Some(&5));
+        assert_eq!(btree.range(&7..&3).collect::<Vec<_>>(), vec![&7, &5]);
+        assert!(avl.validate().is_ok());
+        assert!(rb.validate().is_ok());
+        assert!(btree.validate().is_ok());
+
+        let by_length: Box<dyn Compare<String>> =
+            Box::new(|a: &String, b: &String| a.len().cmp(&b.len()));
+        let mut words = AVLTree::with_comparator(by_length);
+        for word in ["pear", "fig", "banana", "kiwi"] {
+            words.insert(word.to_string());
+        }
+        assert_eq!(words.len(), 3);
+        assert!(words.contains(&"plum".to_string()));
+        assert_eq!(words.pop_min(), Some("fig".to_string()));
+    }
 }